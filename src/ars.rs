@@ -0,0 +1,253 @@
+use super::board::Field;
+use super::rotation;
+use super::rotation::{Direction, RotationSystem};
+use super::tetromino::{
+    Point,
+    Rotation,
+    Tetromino,
+    TetrominoType,
+    MINOS,
+    ROTS,
+    TYPES,
+};
+
+/// The Arika Rotation System used by the Tetris: The Grand Master games. Tetrominos
+/// rest on the bottom of their bounding box, and a blocked rotation may only be kicked
+/// a single column to the right or left. The I Tetromino is never kicked
+pub struct Ars;
+
+impl Ars {
+
+    /// Initializes a new Ars struct
+    pub fn new() -> Self {
+        Ars
+    }
+
+    /// Determines if the center column rule prevents a rotated L, J or T Tetromino from
+    /// being kicked. Reading its bounding box from left to right and top to bottom, the
+    /// rule applies if the first mino that is blocked lies in the center column
+    fn is_center_blocked(field: &Field, rotated: &Tetromino) -> bool {
+        match rotated.tetromino_type() {
+            TetrominoType::J | TetrominoType::L | TetrominoType::T => { },
+            _ => return false,
+        }
+
+        let mut minos = rotated.minos();
+        minos.sort_by_key(|mino| (mino.y, mino.x));
+
+        minos.iter()
+            .find(|&&mino| rotation::is_blocked(field, rotated.origin() + mino))
+            .is_some_and(|mino| mino.x == 1)
+    }
+}
+
+impl Default for Ars {
+    fn default() -> Self {
+        Ars::new()
+    }
+}
+
+impl RotationSystem for Ars {
+    fn tetromino(&self, origin: Point, tetromino_type: TetrominoType, rot: Rotation) -> Tetromino {
+        Tetromino::from_minos(origin, tetromino_type, rot, SHAPES[tetromino_type as usize][rot as usize])
+    }
+
+    fn spawn_point(&self, _tetromino_type: TetrominoType, width: usize) -> Point {
+        Point { x: rotation::center(width, BOX_WIDTH), y: SPAWN_ROW }
+    }
+
+    fn rotate(&self, field: &Field, tetromino: &Tetromino, dir: Direction) -> Option<(Tetromino, usize)> {
+
+        // Tetrominos can't be rotated by 180 degrees
+        if dir == Direction::Half {
+            return None;
+        }
+
+        let rot = rotation::next_rotation(tetromino.rot(), dir);
+        let rotated = self.tetromino(tetromino.origin(), tetromino.tetromino_type(), rot);
+
+        if rotation::fits(field, &rotated, Point { x: 0, y: 0 }) {
+            return Some((rotated, 0));
+        }
+
+        if rotated.tetromino_type() == TetrominoType::I || Ars::is_center_blocked(field, &rotated) {
+            return None;
+        }
+
+        for (i, &test) in KICKS.iter().enumerate() {
+            if rotation::fits(field, &rotated, test) {
+                let mut kicked = rotated;
+                kicked.set_origin(rotated.origin() + test);
+                return Some((kicked, i + 1));
+            }
+        }
+
+        None
+    }
+}
+
+// Tetrominos spawn in a 4x4 bounding box whose top is two rows above the skyline
+const BOX_WIDTH: usize = 4;
+const SPAWN_ROW: isize = -2;
+
+// The wall kicks tested after a rotation in place fails
+const KICKS: [Point; 2] = [Point { x: 1, y: 0 }, Point { x: -1, y: 0 }];
+
+// The minos of each Tetromino in each Rotation, ordered the same as TetrominoType
+const SHAPES: [[[Point; MINOS]; ROTS]; TYPES] = [
+    // I
+    [
+        [Point { x: 0, y: 1 }, Point { x: 1, y: 1 }, Point { x: 2, y: 1 }, Point { x: 3, y: 1 }],
+        [Point { x: 2, y: 0 }, Point { x: 2, y: 1 }, Point { x: 2, y: 2 }, Point { x: 2, y: 3 }],
+        [Point { x: 0, y: 1 }, Point { x: 1, y: 1 }, Point { x: 2, y: 1 }, Point { x: 3, y: 1 }],
+        [Point { x: 2, y: 0 }, Point { x: 2, y: 1 }, Point { x: 2, y: 2 }, Point { x: 2, y: 3 }],
+    ],
+
+    // J
+    [
+        [Point { x: 0, y: 1 }, Point { x: 1, y: 1 }, Point { x: 2, y: 1 }, Point { x: 2, y: 2 }],
+        [Point { x: 1, y: 0 }, Point { x: 1, y: 1 }, Point { x: 0, y: 2 }, Point { x: 1, y: 2 }],
+        [Point { x: 0, y: 1 }, Point { x: 0, y: 2 }, Point { x: 1, y: 2 }, Point { x: 2, y: 2 }],
+        [Point { x: 1, y: 0 }, Point { x: 2, y: 0 }, Point { x: 1, y: 1 }, Point { x: 1, y: 2 }],
+    ],
+
+    // L
+    [
+        [Point { x: 0, y: 1 }, Point { x: 1, y: 1 }, Point { x: 2, y: 1 }, Point { x: 0, y: 2 }],
+        [Point { x: 0, y: 0 }, Point { x: 1, y: 0 }, Point { x: 1, y: 1 }, Point { x: 1, y: 2 }],
+        [Point { x: 2, y: 1 }, Point { x: 0, y: 2 }, Point { x: 1, y: 2 }, Point { x: 2, y: 2 }],
+        [Point { x: 1, y: 0 }, Point { x: 1, y: 1 }, Point { x: 1, y: 2 }, Point { x: 2, y: 2 }],
+    ],
+
+    // O
+    [
+        [Point { x: 1, y: 1 }, Point { x: 2, y: 1 }, Point { x: 1, y: 2 }, Point { x: 2, y: 2 }],
+        [Point { x: 1, y: 1 }, Point { x: 2, y: 1 }, Point { x: 1, y: 2 }, Point { x: 2, y: 2 }],
+        [Point { x: 1, y: 1 }, Point { x: 2, y: 1 }, Point { x: 1, y: 2 }, Point { x: 2, y: 2 }],
+        [Point { x: 1, y: 1 }, Point { x: 2, y: 1 }, Point { x: 1, y: 2 }, Point { x: 2, y: 2 }],
+    ],
+
+    // S
+    [
+        [Point { x: 1, y: 1 }, Point { x: 2, y: 1 }, Point { x: 0, y: 2 }, Point { x: 1, y: 2 }],
+        [Point { x: 0, y: 0 }, Point { x: 0, y: 1 }, Point { x: 1, y: 1 }, Point { x: 1, y: 2 }],
+        [Point { x: 1, y: 1 }, Point { x: 2, y: 1 }, Point { x: 0, y: 2 }, Point { x: 1, y: 2 }],
+        [Point { x: 0, y: 0 }, Point { x: 0, y: 1 }, Point { x: 1, y: 1 }, Point { x: 1, y: 2 }],
+    ],
+
+    // T
+    [
+        [Point { x: 0, y: 1 }, Point { x: 1, y: 1 }, Point { x: 2, y: 1 }, Point { x: 1, y: 2 }],
+        [Point { x: 1, y: 0 }, Point { x: 0, y: 1 }, Point { x: 1, y: 1 }, Point { x: 1, y: 2 }],
+        [Point { x: 1, y: 1 }, Point { x: 0, y: 2 }, Point { x: 1, y: 2 }, Point { x: 2, y: 2 }],
+        [Point { x: 1, y: 0 }, Point { x: 1, y: 1 }, Point { x: 2, y: 1 }, Point { x: 1, y: 2 }],
+    ],
+
+    // Z
+    [
+        [Point { x: 0, y: 1 }, Point { x: 1, y: 1 }, Point { x: 1, y: 2 }, Point { x: 2, y: 2 }],
+        [Point { x: 2, y: 0 }, Point { x: 1, y: 1 }, Point { x: 2, y: 1 }, Point { x: 1, y: 2 }],
+        [Point { x: 0, y: 1 }, Point { x: 1, y: 1 }, Point { x: 1, y: 2 }, Point { x: 2, y: 2 }],
+        [Point { x: 2, y: 0 }, Point { x: 1, y: 1 }, Point { x: 2, y: 1 }, Point { x: 1, y: 2 }],
+    ],
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TYPES: [TetrominoType; 7] = [
+        TetrominoType::I,
+        TetrominoType::J,
+        TetrominoType::L,
+        TetrominoType::O,
+        TetrominoType::S,
+        TetrominoType::T,
+        TetrominoType::Z,
+    ];
+
+    const ROTATIONS: [Rotation; ROTS] = [Rotation::Spawn, Rotation::Right, Rotation::Rot2, Rotation::Left];
+
+    // Far enough from the walls that every wall kick stays inside the field
+    const ORIGIN: Point = Point { x: 3, y: 10 };
+
+    /// Gets an empty field apart from blocks at some offsets from the origin
+    fn field_with(blocks: &[Point]) -> Field {
+        let mut field = vec![vec![None; 10]; 22];
+
+        for &block in blocks.iter() {
+            let pos = ORIGIN + block;
+            field[pos.y as usize][pos.x as usize] = Some(TetrominoType::Z);
+        }
+
+        field
+    }
+
+    #[test]
+    fn rotation_in_open_field() {
+        let ars = Ars::new();
+        let field = field_with(&[]);
+
+        for &tetromino_type in TYPES.iter() {
+            for &rot in ROTATIONS.iter() {
+                let tetromino = ars.tetromino(ORIGIN, tetromino_type, rot);
+
+                for &dir in [Direction::Clockwise, Direction::CounterClockwise].iter() {
+                    let (rotated, kick) = ars.rotate(&field, &tetromino, dir).unwrap();
+
+                    assert_eq!(rotated.rot(), rotation::next_rotation(rot, dir));
+                    assert_eq!(rotated.origin(), ORIGIN);
+                    assert_eq!(kick, 0);
+                }
+
+                assert!(ars.rotate(&field, &tetromino, Direction::Half).is_none());
+            }
+        }
+    }
+
+    #[test]
+    fn kicks_off_the_wall() {
+        let ars = Ars::new();
+        let field = vec![vec![None; 10]; 22];
+
+        // A T pointing left against the wall has to move right to point up
+        let tetromino = ars.tetromino(Point { x: -1, y: 10 }, TetrominoType::T, Rotation::Left);
+        let (rotated, kick) = ars.rotate(&field, &tetromino, Direction::Clockwise).unwrap();
+
+        assert_eq!(rotated.rot(), Rotation::Spawn);
+        assert_eq!(rotated.origin(), Point { x: 0, y: 10 });
+        assert_eq!(kick, 1);
+    }
+
+    #[test]
+    fn center_column_rule() {
+        let ars = Ars::new();
+        let tetromino = ars.tetromino(ORIGIN, TetrominoType::L, Rotation::Spawn);
+
+        // A block in the left column lets the L kick to the right
+        let field = field_with(&[Point { x: 0, y: 0 }]);
+        let (rotated, kick) = ars.rotate(&field, &tetromino, Direction::Clockwise).unwrap();
+
+        assert_eq!(rotated.origin(), ORIGIN + Point { x: 1, y: 0 });
+        assert_eq!(kick, 1);
+
+        // A block in the center column stops it, even though the same kick would fit.
+        // The rule applies to the T as well
+        let field = field_with(&[Point { x: 1, y: 0 }]);
+        assert!(ars.rotate(&field, &tetromino, Direction::Clockwise).is_none());
+
+        let tetromino = ars.tetromino(ORIGIN, TetrominoType::T, Rotation::Spawn);
+        assert!(ars.rotate(&field, &tetromino, Direction::Clockwise).is_none());
+    }
+
+    #[test]
+    fn i_is_never_kicked() {
+        let ars = Ars::new();
+        let field = field_with(&[Point { x: 2, y: 0 }]);
+        let tetromino = ars.tetromino(ORIGIN, TetrominoType::I, Rotation::Spawn);
+
+        // Moving a column to either side would leave room to stand the I up
+        assert!(rotation::fits(&field, &ars.tetromino(ORIGIN, TetrominoType::I, Rotation::Right), Point { x: 1, y: 0 }));
+        assert!(ars.rotate(&field, &tetromino, Direction::Clockwise).is_none());
+    }
+}
@@ -0,0 +1,280 @@
+extern crate toml;
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::input::Key;
+
+/// Something the player can do by pressing a key
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Action {
+    MoveLeft,
+    MoveRight,
+    SoftDrop,
+    HardDrop,
+    RotateClockwise,
+    RotateCounterClockwise,
+    RotateHalf,
+    Hold,
+    Pause,
+    Restart,
+    Quit,
+}
+
+// The name of each Action within a config file
+const ACTIONS: [(Action, &str); 11] = [
+    (Action::MoveLeft, "move_left"),
+    (Action::MoveRight, "move_right"),
+    (Action::SoftDrop, "soft_drop"),
+    (Action::HardDrop, "hard_drop"),
+    (Action::RotateClockwise, "rotate_cw"),
+    (Action::RotateCounterClockwise, "rotate_ccw"),
+    (Action::RotateHalf, "rotate_180"),
+    (Action::Hold, "hold"),
+    (Action::Pause, "pause"),
+    (Action::Restart, "restart"),
+    (Action::Quit, "quit"),
+];
+
+// The name of each Key other than single characters
+const KEYS: [(Key, &str); 9] = [
+    (Key::Left, "left"),
+    (Key::Right, "right"),
+    (Key::Up, "up"),
+    (Key::Down, "down"),
+    (Key::Enter, "enter"),
+    (Key::Esc, "esc"),
+    (Key::Backspace, "backspace"),
+    (Key::Shift, "shift"),
+    (Key::Char(' '), "space"),
+];
+
+/// The keys bound to each Action
+pub struct Bindings {
+    keys: Vec<(Key, Action)>,
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        Bindings {
+            keys: vec![
+                (Key::Left, Action::MoveLeft),
+                (Key::Right, Action::MoveRight),
+                (Key::Down, Action::SoftDrop),
+                (Key::Char('c'), Action::HardDrop),
+                (Key::Char('x'), Action::RotateClockwise),
+                (Key::Char('z'), Action::RotateCounterClockwise),
+                (Key::Char('a'), Action::RotateHalf),
+                (Key::Char(' '), Action::Hold),
+                (Key::Char('p'), Action::Pause),
+                (Key::Char('r'), Action::Restart),
+                (Key::Esc, Action::Quit),
+            ],
+        }
+    }
+}
+
+impl Bindings {
+
+    /// Loads the Bindings from a config file. Without a path, the config file in the
+    /// user's config directory is used if there is one, and the defaults otherwise
+    pub fn load(path: Option<&Path>) -> Result<Self, String> {
+        let path = match path {
+            Some(path) => path.to_path_buf(),
+
+            None => match config_path() {
+                Some(ref path) if path.is_file() => path.clone(),
+                _ => return Ok(Bindings::default()),
+            },
+        };
+
+        let config = fs::read_to_string(&path)
+            .map_err(|e| format!("Unable to read {}: {}", path.display(), e))?;
+
+        Bindings::parse(&config).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// Parses the Bindings from the contents of a config file. Each Action listed
+    /// in the [bindings] table replaces its default keys with the given ones, taking
+    /// them from any Action they were bound to by default
+    pub fn parse(config: &str) -> Result<Self, String> {
+        let config = config.parse::<toml::Table>().map_err(|e| e.to_string())?;
+        let mut bound = Vec::new();
+
+        for (section, value) in config.iter() {
+            if section != "bindings" {
+                return Err(format!("Unknown section '{}'", section));
+            }
+
+            let table = value.as_table().ok_or("'bindings' must be a table")?;
+
+            for (name, value) in table.iter() {
+                let action = ACTIONS.iter()
+                    .find(|&&(_, n)| n == name)
+                    .map(|&(action, _)| action)
+                    .ok_or_else(|| format!("Unknown action '{}'", name))?;
+
+                let keys = match *value {
+                    toml::Value::String(ref key) => vec![parse_key(key)?],
+
+                    toml::Value::Array(ref keys) => {
+                        let mut parsed = Vec::new();
+
+                        for key in keys.iter() {
+                            let key = key.as_str()
+                                .ok_or_else(|| format!("The keys for '{}' must be strings", name))?;
+
+                            parsed.push(parse_key(key)?);
+                        }
+
+                        parsed
+                    },
+
+                    _ => return Err(format!("The keys for '{}' must be a string or an array of strings", name)),
+                };
+
+                bound.push((action, keys));
+            }
+        }
+
+        // Unbind every listed Action and key first, so that keys can be swapped between
+        // Actions and only the keys listed together can conflict
+        let listed = bound.iter().flat_map(|(_, keys)| keys.iter().cloned()).collect::<Vec<_>>();
+
+        let mut bindings = Bindings::default();
+        bindings.keys.retain(|&(key, action)| bound.iter().all(|&(a, _)| a != action) && !listed.contains(&key));
+
+        for &(action, ref keys) in bound.iter() {
+            bindings.bind(action, keys)?;
+        }
+
+        if !bindings.keys.iter().any(|&(_, action)| action == Action::Quit) {
+            return Err(String::from("'quit' must be bound to at least one key"));
+        }
+
+        Ok(bindings)
+    }
+
+    /// Gets the Action bound to a key, if any
+    pub fn action(&self, key: Key) -> Option<Action> {
+        self.keys.iter()
+            .find(|&&(k, _)| k == key)
+            .map(|&(_, action)| action)
+    }
+
+    /// Binds keys to an Action. Fails if a key is already bound to another Action
+    fn bind(&mut self, action: Action, keys: &[Key]) -> Result<(), String> {
+        for &key in keys.iter() {
+            match self.action(key) {
+                Some(other) if other == action => { },
+
+                Some(other) => return Err(format!("'{}' is bound to both '{}' and '{}'",
+                                                  key_name(key), action_name(other), action_name(action))),
+
+                None => self.keys.push((key, action)),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Gets the path of the config file in the user's config directory, following the
+/// XDG base directory specification
+pub fn config_path() -> Option<PathBuf> {
+    let dir = match env::var_os("XDG_CONFIG_HOME") {
+        Some(ref dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(env::var_os("HOME")?).join(".config"),
+    };
+
+    Some(dir.join("yatc-rs").join("config.toml"))
+}
+
+/// Parses the name of a key, which is either a single character or a named key
+fn parse_key(name: &str) -> Result<Key, String> {
+    if let Some(&(key, _)) = KEYS.iter().find(|&&(_, n)| n.eq_ignore_ascii_case(name)) {
+        return Ok(key);
+    }
+
+    let mut chars = name.chars();
+
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Ok(Key::Char(c)),
+        _ => Err(format!("Unknown key '{}'", name)),
+    }
+}
+
+/// Gets the name of a key as it is written in a config file
+fn key_name(key: Key) -> String {
+    match KEYS.iter().find(|&&(k, _)| k == key) {
+        Some(&(_, name)) => name.to_string(),
+        None => match key {
+            Key::Char(c) => c.to_string(),
+            _ => format!("{:?}", key),
+        },
+    }
+}
+
+/// Gets the name of an Action as it is written in a config file
+fn action_name(action: Action) -> &'static str {
+    ACTIONS.iter()
+        .find(|&&(a, _)| a == action)
+        .map(|&(_, name)| name)
+        .unwrap_or("")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_without_bindings() {
+        let bindings = Bindings::parse("").unwrap();
+        assert_eq!(bindings.action(Key::Char(' ')), Some(Action::Hold));
+        assert_eq!(bindings.action(Key::Esc), Some(Action::Quit));
+    }
+
+    #[test]
+    fn takes_default_key() {
+        let bindings = Bindings::parse("[bindings]\nhard_drop = \"space\"").unwrap();
+        assert_eq!(bindings.action(Key::Char(' ')), Some(Action::HardDrop));
+        assert_eq!(bindings.action(Key::Char('c')), None);
+        assert_eq!(bindings.action(Key::Char('x')), Some(Action::RotateClockwise));
+    }
+
+    #[test]
+    fn swaps_keys() {
+        let bindings = Bindings::parse("[bindings]\nhard_drop = \"space\"\nhold = [\"shift\", \"c\"]").unwrap();
+        assert_eq!(bindings.action(Key::Char(' ')), Some(Action::HardDrop));
+        assert_eq!(bindings.action(Key::Shift), Some(Action::Hold));
+        assert_eq!(bindings.action(Key::Char('c')), Some(Action::Hold));
+    }
+
+    #[test]
+    fn unknown_action() {
+        let error = Bindings::parse("[bindings]\nfly = \"f\"").err();
+        assert_eq!(error.as_deref(), Some("Unknown action 'fly'"));
+    }
+
+    #[test]
+    fn unknown_key() {
+        let error = Bindings::parse("[bindings]\nhold = \"tab\"").err();
+        assert_eq!(error.as_deref(), Some("Unknown key 'tab'"));
+    }
+
+    #[test]
+    fn conflicting_keys() {
+        let error = Bindings::parse("[bindings]\nhold = \"v\"\nhard_drop = \"v\"").err();
+        assert_eq!(error.as_deref(), Some("'v' is bound to both 'hard_drop' and 'hold'"));
+    }
+
+    #[test]
+    fn missing_quit() {
+        let error = Bindings::parse("[bindings]\npause = \"esc\"").err();
+        assert_eq!(error.as_deref(), Some("'quit' must be bound to at least one key"));
+
+        let error = Bindings::parse("[bindings]\nquit = []").err();
+        assert_eq!(error.as_deref(), Some("'quit' must be bound to at least one key"));
+    }
+}
@@ -1,20 +1,36 @@
-extern crate rand;
-
-use self::rand::{thread_rng, Rng};
-
-use super::srs;
-use super::srs::Direction;
+use std::collections::VecDeque;
+use std::mem;
+
+use super::mode::{GameMode, Marathon};
+use super::randomizer::Randomizer;
+use super::rotation;
+use super::rotation::{Direction, RotationSystem};
+use super::scoring;
+use super::scoring::{Clear, Spin};
 use super::tetromino::{
     Point,
-    Rotation, 
     Tetromino, 
     TetrominoType, 
 };
 
+/// The default width of the field
 pub const WIDTH: usize = 10;
+
+/// The default height of the field, including the rows above the skyline
 pub const HEIGHT: usize = 22;
 
-const SPAWN: Point = Point { x: 3, y: 0 };
+/// The default first row of the field that is visible. The rows above it are
+/// only there for Tetrominos to spawn into
+pub const SKYLINE: usize = 2;
+
+/// The smallest width and visible height of the field, so that every Tetromino fits
+pub const MIN_SIZE: usize = 4;
+
+/// The maximum number of upcoming Tetrominos that can be previewed
+pub const PREVIEWS: usize = 6;
+
+/// The number of frames the board is advanced by every second
+pub const FPS: u64 = 60;
 
 const LEFT: Point = Point { x: -1, y: 0 };
 const RIGHT: Point = Point { x: 1, y: 0 };
@@ -23,142 +39,545 @@ const DOWN: Point = Point { x: 0, y: 1 };
 const SOFT_DROP: usize = 1;
 const HARD_DROP: usize = 2;
 
-pub type Field = [[Option<TetrominoType>; WIDTH]; HEIGHT];
+// Default lock delay in frames and reset limit as specified by the guideline
+const LOCK_DELAY: usize = 30;
+const LOCK_RESETS: usize = 15;
+
+// The number of frames it takes a Tetromino to fall a single row at each level
+const GRAVITY: [usize; 20] = [ 48, 43, 38, 33, 28,
+                               23, 18, 13,  8,  6,
+                                5,  5,  5,  4,  4,
+                                4,  3,  3,  3,  2, ];
+
+/// The locked blocks of a field, indexed by row and then column
+pub type Field = Vec<Vec<Option<TetrominoType>>>;
+
+/// The ways in which moving or rotating a grounded Tetromino resets its lock delay
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum LockReset {
+
+    /// Every move or rotation resets the lock delay
+    Infinity,
+
+    /// Only moving down to a new lowest row resets the lock delay
+    Step,
+
+    /// Every move or rotation resets the lock delay up to a limited number of times
+    Move(usize),
+}
+
+/// The ways in which the stack can top out, ending the game
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum TopOut {
+
+    /// A Tetromino spawned overlapping the stack
+    BlockOut,
+
+    /// A Tetromino locked entirely above the skyline
+    LockOut,
+
+    /// A Tetromino locked partly above the skyline, if partial lock outs are enabled
+    PartialLockOut,
+}
+
+/// The stages that the board goes through between one Tetromino and the next
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Phase {
+
+    /// The current Tetromino is falling under the player's control
+    Falling,
 
-/// A struct representing a 10x22 Tetris board
+    /// The lines that were just cleared are shown before being removed,
+    /// for a number of frames so far
+    LineClear(usize),
+
+    /// The next Tetromino is waiting to spawn, for a number of frames so far
+    Entry(usize),
+}
+
+/// Something that happened on the board, which is recorded for an Engine to report
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Event {
+
+    /// A Tetromino locked into the stack where it came to rest, along with the kind
+    /// of T-spin that put it there
+    Locked(Tetromino, Spin),
+
+    /// Lines were cleared by the last Tetromino to lock
+    Cleared(Clear),
+
+    /// The stack topped out, ending the game
+    ToppedOut(TopOut),
+
+    /// The goal of the GameMode was reached, ending the game
+    Completed,
+}
+
+/// A struct representing a Tetris board, which is 10x22 unless chosen otherwise
 pub struct Board {
     field: Field,
+    width: usize,
+    height: usize,
+    skyline: usize,
     curr: Tetromino,
     hold: Option<Tetromino>,
     is_hold_locked: bool,
     ghost: Tetromino,
-    next: Vec<TetrominoType>,
-    is_topped_out: bool,
+    next: VecDeque<TetrominoType>,
+    randomizer: Box<dyn Randomizer>,
+    rotation: Box<dyn RotationSystem>,
+    mode: Box<dyn GameMode>,
+    top_out: Option<TopOut>,
+    is_complete: bool,
+    partial_lock_out: bool,
+    phase: Phase,
+    entry_delay: usize,
+    clear_delay: usize,
+    clearing: Vec<usize>,
     score: usize,
     level: usize,
     cleared: usize,
+    pieces: usize,
+    fall: f64,
+    frames: usize,
+    lock_delay: usize,
+    lock_reset: LockReset,
+    lock_timer: Option<usize>,
+    lock_resets: usize,
+    lowest: isize,
+    irs: bool,
+    ihs: bool,
+    initial_rotation: Option<Direction>,
+    is_initial_hold: bool,
+    buffered_rotation: Option<Direction>,
+    is_hold_buffered: bool,
+    last_rotation: Option<(Direction, usize)>,
+    last_clear: Option<Clear>,
+    combo: Option<usize>,
+    is_back_to_back: bool,
+    perfect_clears: usize,
+    records_events: bool,
+    events: Vec<Event>,
 }
 
 impl Board {
 
-    /// Initializes a new Board struct
-    pub fn new() -> Self {
-
-        // Create a sequence of pieces and shuffle them
-        let mut next = vec![
-            TetrominoType::I, 
-            TetrominoType::J, 
-            TetrominoType::L, 
-            TetrominoType::O, 
-            TetrominoType::S, 
-            TetrominoType::T, 
-            TetrominoType::Z,
-        ];
+    /// Initializes a new Board struct that draws its pieces from a Randomizer and
+    /// shapes and rotates them with a RotationSystem
+    pub fn new(randomizer: Box<dyn Randomizer>, rotation: Box<dyn RotationSystem>) -> Self {
+        Board::with_size(randomizer, rotation, WIDTH, HEIGHT, SKYLINE)
+    }
 
-        let mut rng = thread_rng();
-        rng.shuffle(&mut next);
+    /// Initializes a new Board struct with a field of a specified width and height,
+    /// whose rows above the skyline are hidden. Tetrominos spawn just above the skyline,
+    /// so it must be at least 2. Panics if the field is too small for every Tetromino
+    pub fn with_size(mut randomizer: Box<dyn Randomizer>, rotation: Box<dyn RotationSystem>,
+                     width: usize, height: usize, skyline: usize) -> Self {
+        assert!(skyline >= SKYLINE, "the skyline must be at least {} rows down", SKYLINE);
+        assert!(width >= MIN_SIZE && height >= skyline + MIN_SIZE,
+                "a field {} wide with {} visible rows is too small", width, height.saturating_sub(skyline));
 
-        let tetromino = Tetromino::new(SPAWN, next.pop().unwrap(), Rotation::Spawn);
+        let tetromino = rotation.spawn(randomizer.next(), width, skyline);
 
         let mut board = Board {
-            field: [[None; WIDTH]; HEIGHT],
+            field: vec![vec![None; width]; height],
+            width: width,
+            height: height,
+            skyline: skyline,
             curr: tetromino,
             hold: None,
             is_hold_locked: false,
             ghost: Tetromino::new_ghost(&tetromino),
-            next: next,
-            is_topped_out: false,
+            next: VecDeque::with_capacity(PREVIEWS + 1),
+            randomizer: randomizer,
+            rotation: rotation,
+            mode: Box::new(Marathon::endless()),
+            top_out: None,
+            is_complete: false,
+            partial_lock_out: false,
+            phase: Phase::Falling,
+            entry_delay: 0,
+            clear_delay: 0,
+            clearing: vec![],
             score: 0,
             cleared: 0,
+            pieces: 0,
             level: 0,
+            fall: 0.0,
+            frames: 0,
+            lock_delay: LOCK_DELAY,
+            lock_reset: LockReset::Move(LOCK_RESETS),
+            lock_timer: None,
+            lock_resets: 0,
+            lowest: tetromino.origin().y,
+            irs: true,
+            ihs: true,
+            initial_rotation: None,
+            is_initial_hold: false,
+            buffered_rotation: None,
+            is_hold_buffered: false,
+            last_rotation: None,
+            last_clear: None,
+            combo: None,
+            is_back_to_back: false,
+            perfect_clears: 0,
+            records_events: false,
+            events: vec![],
         };
 
-        board.add_current();
-        board.drop_ghost(false);
+        board.fill_next();
+        board.check_spawn();
+        board.drop_ghost();
         board
     }
 
-    /// Applies gravity and clears any lines
-    pub fn tick(&mut self) {
-        self.apply_gravity();
+    /// Advances the board by a single frame. Any player input for the frame must be
+    /// applied beforehand, after which gravity is applied and the lock delay counted,
+    /// or the line clear delay or entry delay counted down. The GameMode then decides
+    /// if the game is complete
+    pub fn update(&mut self) {
+        if self.is_over() {
+            return;
+        }
+
+        self.frames += 1;
+
+        match self.phase {
+            Phase::Falling => {
+                self.apply_gravity();
+                self.update_lock();
+            },
+
+            Phase::LineClear(frames) => {
+                if frames + 1 >= self.clear_delay {
+                    self.collapse_lines();
+                    self.enter();
+                } else {
+                    self.phase = Phase::LineClear(frames + 1);
+                }
+            },
+
+            Phase::Entry(frames) => {
+                if frames + 1 >= self.entry_delay {
+                    self.spawn();
+                } else {
+                    self.phase = Phase::Entry(frames + 1);
+                }
+            },
+        }
+
+        self.is_complete = self.mode.is_complete(self);
+
+        if self.is_complete {
+            self.record(Event::Completed);
+        }
+    }
+
+    /// Records an Event, if events are being recorded
+    fn record(&mut self, event: Event) {
+        if self.records_events {
+            self.events.push(event);
+        }
+    }
+
+    /// Gets the gravity for the current level in rows per frame
+    pub fn gravity(&self) -> f64 {
+        let level = if self.level < GRAVITY.len() { self.level } else { GRAVITY.len() - 1 };
+        1.0 / GRAVITY[level] as f64
     }
 
-    /// Applies gravity to the field
+    /// Applies a frame of gravity to the field. Gravity accumulates until it adds
+    /// up to at least a whole row, so a Tetromino may fall several rows in a frame
     fn apply_gravity(&mut self) {
-        let offset = DOWN;
+        if self.mode.has_gravity() {
+            self.fall += self.gravity();
+        }
 
-        if self.is_moveable(offset) {
-            self.do_move(offset);
+        while self.fall >= 1.0 {
+            if !self.is_moveable(DOWN) {
+                self.fall = 0.0;
+                break;
+            }
+
+            self.do_move(DOWN);
             self.score += SOFT_DROP;
+            self.fall -= 1.0;
+        }
+
+        self.reset_lock(false);
+    }
+
+    /// Counts a frame of the lock delay, locking the current Tetromino if it has rested
+    /// on the stack for the length of the lock delay or has used up all of its resets
+    fn update_lock(&mut self) {
+        if self.is_moveable(DOWN) {
+            return;
+        }
+
+        let lock_delay = self.lock_delay;
+
+        let expired = match self.lock_timer {
+            Some(ref mut frames) => {
+                *frames += 1;
+                *frames >= lock_delay
+            },
+
+            None => false,
+        };
+
+        let exhausted = match self.lock_reset {
+            LockReset::Move(limit) => self.lock_resets >= limit,
+            _ => false,
+        };
+
+        if expired || exhausted {
+            self.lock();
+        }
+    }
+
+    /// Locks the current Tetromino into place and starts the line clear delay if
+    /// any lines were cleared, or the entry delay of the next Tetromino otherwise
+    fn lock(&mut self) {
+        if self.has_ended() {
+            return;
+        }
+
+        let spin = self.detect_spin();
+        let curr = self.curr;
+        self.record(Event::Locked(curr, spin));
+
+        self.place_current();
+        self.pieces += 1;
+        self.check_lock();
+
+        if self.top_out.is_some() {
+            return;
+        }
+
+        self.is_hold_locked = false;
+        self.clear_lines(spin);
+
+        if !self.clearing.is_empty() && self.clear_delay > 0 {
+            self.phase = Phase::LineClear(0);
         }
 
         else {
-            self.is_hold_locked = false;
-            self.clear_lines();
+            self.collapse_lines();
+            self.enter();
+        }
+    }
+
+    /// Starts the entry delay of the next Tetromino, spawning it straight away if there is none
+    fn enter(&mut self) {
+        if self.entry_delay > 0 {
+            self.phase = Phase::Entry(0);
+        } else {
             self.spawn();
         }
     }
 
-    /// Clears any lines from the field completed by the current Tetromino
-    fn clear_lines(&mut self) {
+    /// Determines if the current Tetromino was spun into place using the 3-corner rule
+    fn detect_spin(&self) -> Spin {
+        let (dir, kick) = match self.last_rotation {
+            Some(rotation) if self.curr.tetromino_type() == TetrominoType::T => rotation,
+            _ => return Spin::None,
+        };
+
+        let minos = self.curr.minos();
+
+        // The center of a T Tetromino is the mino touching all of the others, and the
+        // mino opposite none of the others is the one the Tetromino points towards
+        let center = match minos.iter().find(|&&mino| {
+            minos.iter().all(|&other| (other.x - mino.x).abs() + (other.y - mino.y).abs() <= 1)
+        }) {
+            Some(&center) => center,
+            None => return Spin::None,
+        };
+
+        let arms: Vec<Point> = minos.iter()
+            .filter(|&&mino| mino != center)
+            .map(|&mino| Point { x: mino.x - center.x, y: mino.y - center.y })
+            .collect();
+
+        let facing = match arms.iter().find(|&&arm| !arms.contains(&Point { x: -arm.x, y: -arm.y })) {
+            Some(&facing) => facing,
+            None => return Spin::None,
+        };
+
+        // Gets the position of a corner diagonal to the center, either in front of it or behind
+        let side = Point { x: facing.y, y: facing.x };
+        let pos = self.curr.origin() + center;
+
+        let corner = |forward: isize, across: isize| Point {
+            x: pos.x + (facing.x * forward) + (side.x * across),
+            y: pos.y + (facing.y * forward) + (side.y * across),
+        };
+
+        let front = [corner(1, 1), corner(1, -1)].iter().filter(|&&corner| rotation::is_blocked(&self.field, corner)).count();
+        let back = [corner(-1, 1), corner(-1, -1)].iter().filter(|&&corner| rotation::is_blocked(&self.field, corner)).count();
+
+        if front + back < 3 {
+            return Spin::None;
+        }
+
+        // A T-spin is only a mini if the back corners are the ones filled, unless
+        // the rotation system counts the wall kick that was needed as a full T-spin
+        if front == 2 || self.rotation.is_full_spin(dir, kick) {
+            Spin::Full
+        }
+
+        else {
+            Spin::Mini
+        }
+    }
+
+    /// Updates the lock delay after the current Tetromino has moved. A manipulation
+    /// is a move or rotation made by the player rather than by gravity
+    fn reset_lock(&mut self, manipulated: bool) {
+        let y = self.curr.origin().y;
+
+        // Reaching a new lowest row always restores the full lock delay
+        if y > self.lowest {
+            self.lowest = y;
+            self.lock_timer = None;
+            self.lock_resets = 0;
+        }
+
+        else if manipulated && self.lock_timer.is_some() {
+            match self.lock_reset {
+                LockReset::Infinity => self.lock_timer = Some(0),
+                LockReset::Step => { },
+                LockReset::Move(limit) => {
+                    if self.lock_resets < limit {
+                        self.lock_resets += 1;
+                        self.lock_timer = Some(0);
+                    }
+                },
+            }
+        }
+
+        // The lock delay starts as soon as the Tetromino rests on the stack
+        if self.lock_timer.is_none() && !self.is_moveable(DOWN) {
+            self.lock_timer = Some(0);
+        }
+    }
+
+    /// Clears the gravity and lock delay state for a newly placed Tetromino
+    fn clear_lock(&mut self) {
+        self.last_rotation = None;
+        self.fall = 0.0;
+        self.lock_timer = None;
+        self.lock_resets = 0;
+        self.lowest = self.curr.origin().y;
+    }
+
+    /// Scores any lines completed by the current Tetromino. The lines stay in the
+    /// field until they are collapsed at the end of the line clear delay
+    fn clear_lines(&mut self, spin: Spin) {
         let mut cleared = vec![];
 
         for &mino in self.curr.minos().iter() {
             let row = (self.curr.origin().y + mino.y) as usize;
 
-            if self.is_line(row) {
-                for col in 0..WIDTH {
-                    self.field[row][col] = None;
-                }
-
+            if self.is_line(row) && !cleared.contains(&row) {
                 cleared.push(row);
             }
         }
 
-        // Drop the rows if any line clears were made
-        if cleared.first().is_some() {
-            self.drop_rows(*cleared.first().unwrap(), cleared.len());
-            self.cleared += cleared.len();
-            self.level = self.cleared / 10;
-            self.score_clear(cleared.len());
+        cleared.sort();
+        let mut clear = Clear::new(cleared.len(), spin);
+
+        if clear.lines > 0 && self.is_empty(&cleared) {
+            clear.perfect_clear = true;
+            self.perfect_clears += 1;
+        }
+
+        // Consecutive line clears build up a combo, while difficult line clears
+        // keep the back-to-back chain going until an easier one breaks it
+        if clear.lines > 0 {
+            clear.back_to_back = self.is_back_to_back && clear.is_difficult();
+            self.is_back_to_back = clear.is_difficult();
+            self.combo = Some(self.combo.map_or(0, |combo| combo + 1));
+        }
+
+        else {
+            self.combo = None;
+        }
+
+        if clear.lines > 0 {
+            self.record(Event::Cleared(clear));
+        }
+
+        if clear.is_scoring() {
+            self.score_clear(&clear);
+            self.last_clear = Some(clear);
+        }
+
+        else {
+            self.last_clear = None;
+        }
+
+        // The clear is scored at the level it was made on, before any level up it causes
+        self.cleared += cleared.len();
+        self.level = self.mode.level(self.cleared);
+        self.clearing = cleared;
+    }
+
+    /// Removes the lines being cleared from the field, dropping the rows above them
+    fn collapse_lines(&mut self) {
+
+        // The lines are sorted from top to bottom, so dropping the rows above
+        // one line never moves any of the lines below it
+        for row in mem::take(&mut self.clearing) {
+            self.drop_rows(row, 1);
         }
     }
 
     /// Determines if a specific row in the field is a complete line
     fn is_line(&self, row: usize) -> bool {
-        for col in 0..WIDTH {
-            match self.field[row][col] {
-                Some(TetrominoType::Ghost) | None => return false,
-                _ => continue,
+        for col in 0..self.width {
+            if self.field[row][col].is_none() {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    /// Determines if the field is free of any locked blocks outside of some lines
+    fn is_empty(&self, lines: &[usize]) -> bool {
+        for (y, row) in self.field.iter().enumerate() {
+            if lines.contains(&y) {
+                continue;
+            }
+
+            for cell in row.iter() {
+                if cell.is_some() {
+                    return false;
+                }
             }
         }
 
         true
     }
 
-    /// Drops all rows above the given start row by drop
+    /// Drops all rows above the given start row by drop, overwriting the start row
     fn drop_rows(&mut self, start: usize, drop: usize) {
 
         // Use rev() because Rust doesn't support backwards iteration
         for row in (0..start).rev() {
-            for col in 0..WIDTH {
+            for col in 0..self.width {
                 self.field[row + drop][col] = self.field[row][col].take();
             }
         }
     }
 
-    /// Increases the score based on the number of lines cleared
-    fn score_clear(&mut self, lines: usize) {
+    /// Increases the score based on the lines cleared, any T-spin performed and the combo
+    fn score_clear(&mut self, clear: &Clear) {
+        self.score += clear.points(self.level);
 
-        // The scoring formula used is identical to the NES version
-        self.score += match lines {
-            1 =>   40 * (self.level + 1),
-            2 =>  100 * (self.level + 1),
-            3 =>  300 * (self.level + 1),
-            4 => 1200 * (self.level + 1),
-
-            // 4+ lines cleared is impossible in the current system.
-            _ => 0,
+        if let Some(combo) = self.combo {
+            self.score += scoring::combo_points(combo, self.level);
         }
     }
 
@@ -179,235 +598,276 @@ impl Board {
 
     /// Continually drops the current Tetromino until it locks
     pub fn drop_tetromino(&mut self) {
+        if self.phase != Phase::Falling || self.has_ended() {
+            return;
+        }
+
         while self.is_moveable(DOWN) {
             self.do_move(DOWN);
             self.score += HARD_DROP;
         }
 
-        self.is_hold_locked = false;
-        self.clear_lines();
-        self.spawn();
+        self.lock();
     }
 
     /// Moves the current Tetromino by an (x, y) offset
     fn move_tetromino(&mut self, offset: Point) {
-        if self.is_moveable(offset) {
+        if self.phase == Phase::Falling && !self.has_ended() && self.is_moveable(offset) {
             self.do_move(offset);
 
             if offset != DOWN {
-                self.drop_ghost(true);
+                self.drop_ghost();
             }
+
+            self.reset_lock(offset != DOWN);
         }
     }
 
     /// Determines if an offset to the current Tetromino is possible or not
     fn is_moveable(&self, offset: Point) -> bool {
-        for &mino in self.curr.minos().iter() {
-            let pos = self.curr.origin() + mino + offset;
+        !self.collides(&self.curr, offset)
+    }
 
-            // Determine if the field's boundaries are respected
-            if pos.x < 0 || pos.y < 0 || (pos.x as usize) >= WIDTH || (pos.y as usize) >= HEIGHT {
-                return false;
-            }
+    /// Determines if an offset to a Tetromino would leave the field or overlap the stack
+    fn collides(&self, tetromino: &Tetromino, offset: Point) -> bool {
+        !rotation::fits(&self.field, tetromino, offset)
+    }
 
-            // Determine if the offset causes any overlap for this Mino 
-            match self.field[pos.y as usize][pos.x as usize] {
-                Some(TetrominoType::Ghost) | None => continue,
-                Some(..) if self.overlaps_active(pos) => continue,
-                _ => return false,
-            }
-        }
+    /// Performs the actual movement
+    fn do_move(&mut self, offset: Point) {
+        let origin = self.curr.origin() + offset;
+        self.curr.set_origin(origin);
 
-        true
+        // Any successful movement means the Tetromino was no longer spun into place
+        self.last_rotation = None;
     }
 
-    /// Determines if an offset to the current Tetromino would cause it to 
-    /// only overlap itself or not
-    fn overlaps_active(&self, new: Point) -> bool {
-        for &mino in self.curr.minos().iter() {
-            let pos = self.curr.origin() + mino;
-            if new == pos {
-                return true;
-            }
-        }
+    /// Drops the ghost Tetromino beneath the current Tetromino
+    fn drop_ghost(&mut self) {
+        self.ghost = Tetromino::new_ghost(&self.curr);
 
-        false
+        // Determine the position that the ghost should drop down to
+        while !self.collides(&self.ghost, DOWN) {
+            let origin = self.ghost.origin() + DOWN;
+            self.ghost.set_origin(origin);
+        }
     }
 
-    /// Performs the actual movement
-    fn do_move(&mut self, offset: Point) {
-        let mut minos = self.curr.minos().clone();
+    /// Rotates the current Tetromino in a specified direction. A rotation made while
+    /// no Tetromino is falling is buffered and applied to the next one by IRS
+    pub fn rotate(&mut self, dir: Direction) {
+        if self.has_ended() {
+            return;
+        }
 
-        // Moving down or right requires us to handle the blocks in
-        // reverse order to prevent blocks from being erased
-        match offset {
-            RIGHT | DOWN => minos.reverse(),
-            _ => { },
+        if self.phase != Phase::Falling {
+            self.buffered_rotation = Some(dir);
+            return;
         }
 
-        // Perform the move
-        for &mino in minos.iter() {
-            let org = self.curr.origin() + mino;
-            let pos = org + offset;
+        if let Some((rotated, kick)) = self.rotation.rotate(&self.field, &self.curr, dir) {
+            self.curr = rotated;
+            self.last_rotation = Some((dir, kick));
+            self.drop_ghost();
+            self.reset_lock(true);
+        }
+    }
+
+    /// Moves the current Tetromino into hold. A hold made while no Tetromino
+    /// is falling is buffered and applied to the next one by IHS
+    pub fn hold_tetromino(&mut self) {
+        if self.has_ended() {
+            return;
+        }
 
-            self.field[pos.y as usize][pos.x as usize] = self.field[org.y as usize][org.x as usize].take();
+        if self.phase != Phase::Falling {
+            self.is_hold_buffered = true;
         }
 
-        let origin = self.curr.origin() + offset;
-        self.curr.set_origin(origin);
+        else if !self.is_hold_locked {
+            self.swap_hold();
+            self.check_spawn();
+            self.drop_ghost();
+            self.clear_lock();
+        }
     }
 
-    /// Drops the ghost Tetromino beneath the current Tetromino
-    fn drop_ghost(&mut self, erase: bool) {
+    /// Swaps the current Tetromino with the one in hold, or with the next
+    /// Tetromino in the sequence if nothing is being held
+    fn swap_hold(&mut self) {
+        let curr = self.spawn_tetromino(self.curr.tetromino_type());
 
-        // Remove the existing ghost from the board
-        if erase {
-            for &mino in self.ghost.minos().iter() {
-                let pos = self.ghost.origin() + mino;
+        self.curr = match self.hold {
+            Some(hold) => hold,
+            None => self.pop_next(),
+        };
 
-                if let Some(TetrominoType::Ghost) = self.field[pos.y as usize][pos.x as usize] {
-                    self.field[pos.y as usize][pos.x as usize] = None;
-                }
-            }
-        }
+        self.hold = Some(curr);
+        self.is_hold_locked = true;
+    }
 
-        self.ghost = Tetromino::new_ghost(&self.curr);
+    /// Peeks at up to PREVIEWS of the next Tetrominos in the sequence
+    pub fn peek_next(&self, count: usize) -> Vec<Tetromino> {
+        self.next.iter()
+            .take(count)
+            .map(|&next| self.spawn_tetromino(next))
+            .collect()
+    }
 
-        // Determine the position that the ghost should drop down to
-        while self.is_ghost_moveable() {
-            let origin = self.ghost.origin() + DOWN;
-            self.ghost.set_origin(origin);
+    /// Draws from the Randomizer until the full preview is available
+    fn fill_next(&mut self) {
+        while self.next.len() < PREVIEWS {
+            let next = self.randomizer.next();
+            self.next.push_back(next);
         }
+    }
 
-        // Perform the ghost drop
-        for &mino in self.ghost.minos().iter() {
-            let pos = self.ghost.origin() + mino;
+    /// Takes the next Tetromino in the sequence in its spawn position
+    fn pop_next(&mut self) -> Tetromino {
+        let next = self.next.pop_front().unwrap();
+        self.fill_next();
+        self.spawn_tetromino(next)
+    }
 
-            if !self.overlaps_active(pos) {
-                self.field[pos.y as usize][pos.x as usize] = Some(TetrominoType::Ghost);
-            }
-        }   
+    /// Gets a Tetromino of a specified type in its spawn position on this board
+    fn spawn_tetromino(&self, tetromino_type: TetrominoType) -> Tetromino {
+        self.rotation.spawn(tetromino_type, self.width, self.skyline)
     }
 
-    /// Determines if the ghost Tetromino can be moved down
-    fn is_ghost_moveable(&self) -> bool {
-        for &mino in self.ghost.minos().iter() {
-            let pos = self.ghost.origin() + mino + DOWN;
+    /// Spawns the next Tetromino in the sequence. A hold or rotation that the player is
+    /// holding or buffered during the delays is applied before the Tetromino enters
+    /// play, if IHS or IRS is enabled
+    fn spawn(&mut self) {
+        let is_hold = mem::take(&mut self.is_hold_buffered) || self.is_initial_hold;
+        let rotation = self.buffered_rotation.take().or(self.initial_rotation);
+
+        self.phase = Phase::Falling;
+        self.curr = self.pop_next();
 
-            // Check that the boundary conditions are respected. We do not need
-            // to check for x-axis boundaries because a ghost is located directly
-            // underneath the current Tetromino
-            if pos.y as usize >= HEIGHT {
-                return false;
-            }
+        if self.ihs && is_hold && !self.is_hold_locked {
+            self.swap_hold();
+        }
 
-            // Determine if an acceptable overlap exists. We consider other
-            // ghosts and active Tetrominos to be acceptable for overlaps.
-            match self.field[pos.y as usize][pos.x as usize] {
-                Some(TetrominoType::Ghost) | None => continue,
-                Some(..) if self.overlaps_active(pos) => continue,
-                _ => return false,
+        // An initial rotation that can't be made leaves the Tetromino in its spawn rotation
+        if let Some(dir) = rotation.filter(|_| self.irs) {
+            if let Some((rotated, _)) = self.rotation.rotate(&self.field, &self.curr, dir) {
+                self.curr = rotated;
             }
         }
 
-        true
+        self.check_spawn();
+        self.drop_ghost();
+        self.clear_lock();
     }
 
-    /// Rotates the current Tetromino in a specified direction
-    pub fn rotate(&mut self, dir: Direction) {
-        if let Some((field, rotated)) = srs::rotate(&self.field, &self.curr, dir) {
-            self.field = field;
-            self.curr = rotated;
-            self.drop_ghost(true);
+    /// Determines if the current Tetromino spawned on top of the stack, in which case
+    /// the field has been topped out and the game is over. Modes that can't top out
+    /// clear the field instead
+    fn check_spawn(&mut self) {
+        if !self.collides(&self.curr, Point { x: 0, y: 0 }) {
+            return;
+        }
+
+        if self.mode.can_top_out() {
+            self.top_out = Some(TopOut::BlockOut);
+            self.record(Event::ToppedOut(TopOut::BlockOut));
+        } else {
+            self.field = vec![vec![None; self.width]; self.height];
         }
     }
 
-    /// Moves the current Tetromino into hold
-    pub fn hold_tetromino(&mut self) {
-        if !self.is_hold_locked {
-            let curr = self.curr.clone();
-            let ghost = self.ghost.clone();
-
-            self.remove(curr);
-            self.remove(ghost);
-            
-            self.curr.set_origin(SPAWN);
-
-            if let Some(hold) = self.hold {
-                let temp = Some(self.curr.clone());
-                self.curr = hold;
-                self.hold = temp;
-
-                self.add_current();
-                self.drop_ghost(false);
-            }
+    /// Determines if the current Tetromino locked above the skyline, in which
+    /// case the field has been topped out and the game is over
+    fn check_lock(&mut self) {
+        if !self.mode.can_top_out() {
+            return;
+        }
 
-            else {
-                self.hold = Some(self.curr.clone());
-                self.spawn();
-            }
+        let above = self.curr.minos().iter()
+            .filter(|&&mino| self.curr.origin().y + mino.y < self.skyline as isize)
+            .count();
 
-            self.is_hold_locked = true;
-        } 
-    }
+        if above == self.curr.minos().len() {
+            self.top_out = Some(TopOut::LockOut);
+        }
 
-    // Remove a Tetromino from the field
-    fn remove(&mut self, tetromino: Tetromino) {
-        for &mino in tetromino.minos().iter() {
-            let pos = tetromino.origin() + mino;
-            self.field[pos.y as usize][pos.x as usize] = None;
+        else if above > 0 && self.partial_lock_out {
+            self.top_out = Some(TopOut::PartialLockOut);
         }
-    }
 
-    /// Peeks at the next Tetromino
-    pub fn peek_next(&self) -> Tetromino {
-        Tetromino::new(SPAWN, *self.next.last().unwrap(), Rotation::Spawn)
+        if let Some(top_out) = self.top_out {
+            self.record(Event::ToppedOut(top_out));
+        }
     }
 
-    /// Spawns the next Tetromino in the sequence
-    fn spawn(&mut self) {
-        self.curr = Tetromino::new(SPAWN, self.next.pop().unwrap(), Rotation::Spawn);
-        self.drop_ghost(false);
-        self.add_current();
+    /// Writes the current Tetromino into the stack of locked blocks
+    fn place_current(&mut self) {
+        for &mino in self.curr.minos().iter() {
+            let pos = self.curr.origin() + mino;
 
-        // All of the pieces have been picked, so reshuffle them
-        if self.next.is_empty() {
-            self.next = vec![
-                TetrominoType::I, 
-                TetrominoType::J, 
-                TetrominoType::L, 
-                TetrominoType::O, 
-                TetrominoType::S, 
-                TetrominoType::T, 
-                TetrominoType::Z,
-            ];
+            // Only minos inside the field are placed, in case a RotationSystem spawns
+            // Tetrominos partly outside of a field with a short buffer
+            if pos.x < 0 || pos.y < 0 {
+                continue;
+            }
 
-            let mut rng = thread_rng();
-            rng.shuffle(&mut self.next);
+            if let Some(cell) = self.field.get_mut(pos.y as usize).and_then(|row| row.get_mut(pos.x as usize)) {
+                *cell = Some(self.curr.tetromino_type());
+            }
         }
     }
 
-    /// Adds the current Tetromino to the field
-    fn add_current(&mut self) {
+    /// Composes the stack with the ghost and current Tetromino for rendering. Neither
+    /// is shown during the line clear delay or entry delay
+    pub fn render_field(&self) -> Field {
+        let mut field = self.field.clone();
 
-        // Add the new blocks to the board
-        for &mino in self.curr.minos().iter() {
-            let pos = self.curr.origin() + mino;
+        if self.phase != Phase::Falling {
+            return field;
+        }
 
-            // The field has been topped out and the game is over
-            if self.field[pos.y as usize][pos.x as usize].is_some() {
-                self.is_topped_out = true;
-                break;
-            }
+        for &tetromino in [self.ghost, self.curr].iter() {
+            for &mino in tetromino.minos().iter() {
+                let pos = tetromino.origin() + mino;
 
-            self.field[pos.y as usize][pos.x as usize] = Some(self.curr.tetromino_type());
+                if pos.x >= 0 && pos.y >= 0 && (pos.x as usize) < self.width && (pos.y as usize) < self.height {
+                    field[pos.y as usize][pos.x as usize] = Some(tetromino.tetromino_type());
+                }
+            }
         }
+
+        field
     }
 
     // GETTERS / SETTERS
 
-    pub fn field(&self) -> Field {
-        self.field
+    pub fn field(&self) -> &Field {
+        &self.field
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Gets the height of the field, including the rows above the skyline
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Gets the first row of the field that is visible
+    pub fn skyline(&self) -> usize {
+        self.skyline
+    }
+
+    /// Gets the Tetromino under the player's control. Outside of the Falling phase, this
+    /// is the last Tetromino to lock
+    pub fn current(&self) -> Tetromino {
+        self.curr
+    }
+
+    /// Gets where the current Tetromino would land if it were dropped
+    pub fn ghost(&self) -> Tetromino {
+        self.ghost
     }
 
     pub fn hold(&self) -> Option<Tetromino> {
@@ -415,7 +875,57 @@ impl Board {
     }
 
     pub fn is_topped_out(&self) -> bool {
-        self.is_topped_out
+        self.top_out.is_some()
+    }
+
+    /// Determines if the player reached the goal of the GameMode
+    pub fn is_complete(&self) -> bool {
+        self.is_complete
+    }
+
+    /// Determines if the game has ended, either by topping out or by being completed
+    pub fn is_over(&self) -> bool {
+        self.is_topped_out() || self.is_complete
+    }
+
+    /// Determines if the game has ended, including a goal reached by an input since the
+    /// last frame, before the GameMode has been asked about it
+    fn has_ended(&self) -> bool {
+        self.is_over() || self.mode.is_complete(self)
+    }
+
+    pub fn mode(&self) -> &dyn GameMode {
+        self.mode.as_ref()
+    }
+
+    /// Sets the GameMode deciding how the game progresses and when it ends, which
+    /// should be chosen before the game starts
+    pub fn set_mode(&mut self, mode: Box<dyn GameMode>) {
+        self.mode = mode;
+    }
+
+    /// Gets the way in which the stack topped out, if the game is over
+    pub fn top_out(&self) -> Option<TopOut> {
+        self.top_out
+    }
+
+    pub fn partial_lock_out(&self) -> bool {
+        self.partial_lock_out
+    }
+
+    /// Sets whether locking a Tetromino partly above the skyline ends the game
+    pub fn set_partial_lock_out(&mut self, partial_lock_out: bool) {
+        self.partial_lock_out = partial_lock_out;
+    }
+
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    /// Gets the rows of the lines that are shown during the line clear delay,
+    /// sorted from top to bottom
+    pub fn clearing(&self) -> &[usize] {
+        &self.clearing
     }
 
     pub fn score(&self) -> usize {
@@ -429,4 +939,155 @@ impl Board {
     pub fn cleared(&self) -> usize {
         self.cleared
     }
-}
\ No newline at end of file
+
+    /// Gets the number of Tetrominos that have been locked
+    pub fn pieces(&self) -> usize {
+        self.pieces
+    }
+
+    pub fn last_clear(&self) -> Option<Clear> {
+        self.last_clear
+    }
+
+    pub fn combo(&self) -> Option<usize> {
+        self.combo
+    }
+
+    pub fn is_back_to_back(&self) -> bool {
+        self.is_back_to_back
+    }
+
+    pub fn perfect_clears(&self) -> usize {
+        self.perfect_clears
+    }
+
+    pub fn frames(&self) -> usize {
+        self.frames
+    }
+
+    pub fn lock_delay(&self) -> usize {
+        self.lock_delay
+    }
+
+    pub fn set_lock_delay(&mut self, lock_delay: usize) {
+        self.lock_delay = lock_delay;
+    }
+
+    pub fn entry_delay(&self) -> usize {
+        self.entry_delay
+    }
+
+    /// Sets the number of frames between a Tetromino locking and the next one spawning
+    pub fn set_entry_delay(&mut self, entry_delay: usize) {
+        self.entry_delay = entry_delay;
+    }
+
+    pub fn clear_delay(&self) -> usize {
+        self.clear_delay
+    }
+
+    /// Sets the number of frames that cleared lines are shown for before they are
+    /// removed, which comes before the entry delay
+    pub fn set_clear_delay(&mut self, clear_delay: usize) {
+        self.clear_delay = clear_delay;
+    }
+
+    pub fn irs(&self) -> bool {
+        self.irs
+    }
+
+    pub fn set_irs(&mut self, irs: bool) {
+        self.irs = irs;
+    }
+
+    pub fn ihs(&self) -> bool {
+        self.ihs
+    }
+
+    pub fn set_ihs(&mut self, ihs: bool) {
+        self.ihs = ihs;
+    }
+
+    pub fn initial_rotation(&self) -> Option<Direction> {
+        self.initial_rotation
+    }
+
+    /// Sets the rotation that the player is holding, which is applied to each
+    /// Tetromino as it spawns while IRS is enabled
+    pub fn set_initial_rotation(&mut self, initial_rotation: Option<Direction>) {
+        self.initial_rotation = initial_rotation;
+    }
+
+    pub fn is_initial_hold(&self) -> bool {
+        self.is_initial_hold
+    }
+
+    /// Sets whether the player is holding hold, which holds each Tetromino
+    /// as it spawns while IHS is enabled
+    pub fn set_initial_hold(&mut self, is_initial_hold: bool) {
+        self.is_initial_hold = is_initial_hold;
+    }
+
+    pub fn lock_reset(&self) -> LockReset {
+        self.lock_reset
+    }
+
+    pub fn set_lock_reset(&mut self, lock_reset: LockReset) {
+        self.lock_reset = lock_reset;
+    }
+
+    pub fn records_events(&self) -> bool {
+        self.records_events
+    }
+
+    /// Sets whether the board keeps a record of each Event. Events are only recorded
+    /// while something is around to take them, such as an Engine
+    pub fn set_records_events(&mut self, records_events: bool) {
+        self.records_events = records_events;
+    }
+
+    /// Gets the Events recorded since they were last cleared
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    pub fn clear_events(&mut self) {
+        self.events.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::randomizer::Bag;
+    use super::super::srs::{HalfKicks, Srs};
+
+    /// Gets a Board with a field of a specified size
+    fn sized(width: usize, height: usize, skyline: usize) -> Board {
+        Board::with_size(Box::new(Bag::new(1, 42)), Box::new(Srs::new(HalfKicks::Tetrio)), width, height, skyline)
+    }
+
+    #[test]
+    fn smallest_field() {
+        let board = sized(MIN_SIZE, MIN_SIZE + SKYLINE, SKYLINE);
+        assert!(!board.is_over());
+    }
+
+    #[test]
+    #[should_panic(expected = "too small")]
+    fn too_narrow() {
+        sized(MIN_SIZE - 1, HEIGHT, SKYLINE);
+    }
+
+    #[test]
+    #[should_panic(expected = "too small")]
+    fn too_short() {
+        sized(WIDTH, MIN_SIZE - 1 + SKYLINE, SKYLINE);
+    }
+
+    #[test]
+    #[should_panic(expected = "skyline")]
+    fn skyline_too_high() {
+        sized(WIDTH, HEIGHT, SKYLINE - 1);
+    }
+}
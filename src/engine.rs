@@ -0,0 +1,479 @@
+use super::board::{Board, Event};
+use super::randomizer::Bag;
+use super::rotation::Direction;
+use super::srs::{HalfKicks, Srs};
+
+/// Something a bot or other program can do to control a Board. Each Action happens
+/// exactly once when applied, with none of the repeating of a held key
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Action {
+
+    /// Moves the current Tetromino one column to the left
+    MoveLeft,
+
+    /// Moves the current Tetromino one column to the right
+    MoveRight,
+
+    /// Moves the current Tetromino as far to the left as it can go
+    ShiftLeft,
+
+    /// Moves the current Tetromino as far to the right as it can go
+    ShiftRight,
+
+    /// Moves the current Tetromino down a row
+    SoftDrop,
+
+    /// Moves the current Tetromino down as far as it can go without locking it
+    SonicDrop,
+
+    /// Drops the current Tetromino and locks it straight away
+    HardDrop,
+
+    RotateClockwise,
+    RotateCounterClockwise,
+    RotateHalf,
+    Hold,
+}
+
+/// Plays a game on a Board without a terminal, for bots, fuzzing and testing. The
+/// Board is controlled by Actions and advanced by frames, and each call reports the
+/// Events that it caused
+pub struct Engine {
+    board: Board,
+}
+
+impl Engine {
+
+    /// Initializes a new Engine struct that plays on a Board, which may be set up with
+    /// any size, mode, randomizer and rotation system
+    pub fn new(mut board: Board) -> Self {
+        board.set_records_events(true);
+        board.clear_events();
+
+        Engine {
+            board: board,
+        }
+    }
+
+    /// Initializes a new Engine struct playing on a standard Board, with pieces dealt
+    /// from a 7-bag seeded by a seed and rotated by SRS
+    pub fn seeded(seed: u64) -> Self {
+        Engine::new(Board::new(Box::new(Bag::new(1, seed)), Box::new(Srs::new(HalfKicks::Tetrio))))
+    }
+
+    /// Applies an Action to the Board. Returns the Events it caused, such as the
+    /// current Tetromino locking after a hard drop. Nothing happens once the game is over
+    pub fn apply(&mut self, action: Action) -> &[Event] {
+        self.board.clear_events();
+
+        if self.board.is_over() {
+            return self.board.events();
+        }
+
+        match action {
+            Action::MoveLeft               => self.board.left(),
+            Action::MoveRight              => self.board.right(),
+            Action::ShiftLeft              => Engine::repeat(&mut self.board, Board::left),
+            Action::ShiftRight             => Engine::repeat(&mut self.board, Board::right),
+            Action::SoftDrop               => self.board.down(),
+            Action::SonicDrop              => Engine::repeat(&mut self.board, Board::down),
+            Action::HardDrop               => self.board.drop_tetromino(),
+            Action::RotateClockwise        => self.board.rotate(Direction::Clockwise),
+            Action::RotateCounterClockwise => self.board.rotate(Direction::CounterClockwise),
+            Action::RotateHalf             => self.board.rotate(Direction::Half),
+            Action::Hold                   => self.board.hold_tetromino(),
+        }
+
+        self.board.events()
+    }
+
+    /// Advances the Board by a number of frames, stopping early if the game ends.
+    /// Returns the Events that happened along the way, in order
+    pub fn advance(&mut self, frames: usize) -> &[Event] {
+        self.board.clear_events();
+
+        for _ in 0..frames {
+            if self.board.is_over() {
+                break;
+            }
+
+            self.board.update();
+        }
+
+        self.board.events()
+    }
+
+    /// Repeats a move until the current Tetromino stops moving
+    fn repeat(board: &mut Board, step: fn(&mut Board)) {
+        loop {
+            let origin = board.current().origin();
+            step(board);
+
+            if board.current().origin() == origin {
+                return;
+            }
+        }
+    }
+
+    // GETTERS / SETTERS
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    /// Gets the Board to change its settings, such as its mode or delays
+    pub fn board_mut(&mut self) -> &mut Board {
+        &mut self.board
+    }
+
+    /// Stops the Engine, handing back its Board
+    pub fn into_board(mut self) -> Board {
+        self.board.set_records_events(false);
+        self.board.clear_events();
+        self.board
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::board::{LockReset, TopOut};
+    use super::super::mode::Marathon;
+    use super::super::randomizer::Randomizer;
+    use super::super::scoring::{Clear, Spin};
+    use super::super::tetromino::TetrominoType;
+    use super::super::tetromino::TetrominoType::{I, J, L, O, S, T, Z};
+
+    /// Deals Tetrominos in a fixed order, starting again from the first once they run out
+    struct Sequence {
+        pieces: Vec<TetrominoType>,
+        next: usize,
+    }
+
+    impl Randomizer for Sequence {
+        fn next(&mut self) -> TetrominoType {
+            let piece = self.pieces[self.next % self.pieces.len()];
+            self.next += 1;
+            piece
+        }
+    }
+
+    #[test]
+    fn hard_drops_until_block_out() {
+        let mut engine = Engine::seeded(42);
+        let mut locked = vec![];
+
+        // Standing every piece on end in the middle soon leaves no room to spawn
+        loop {
+            engine.apply(Action::RotateClockwise);
+            let events = engine.apply(Action::HardDrop).to_vec();
+
+            match events[0] {
+                Event::Locked(tetromino, spin) => {
+                    assert_eq!(spin, Spin::None);
+                    locked.push(tetromino.tetromino_type());
+                },
+
+                ref event => panic!("{:?} before a lock", event),
+            }
+
+            if engine.board().is_over() {
+                assert_eq!(events[1..], [Event::ToppedOut(TopOut::BlockOut)]);
+                break;
+            }
+
+            assert_eq!(events.len(), 1);
+        }
+
+        assert_eq!(locked, [T, O, I, L, S, J, Z, J]);
+        assert!(engine.apply(Action::HardDrop).is_empty());
+    }
+
+    #[test]
+    fn advance_stops_at_game_over() {
+        let mut engine = Engine::seeded(42);
+
+        // Gravity alone stacks every piece in the middle until one locks out
+        let events = engine.advance(100_000).to_vec();
+        let frames = engine.board().frames();
+
+        assert!(engine.board().is_over());
+        assert!(frames < 100_000);
+        assert_eq!(events.iter().filter(|event| matches!(event, Event::Locked(..))).count(), 14);
+        assert_eq!(events.last(), Some(&Event::ToppedOut(TopOut::LockOut)));
+
+        assert!(engine.advance(60).is_empty());
+        assert_eq!(engine.board().frames(), frames);
+    }
+
+    #[test]
+    fn inputs_stop_when_the_game_ends() {
+        let pieces = Box::new(Sequence { pieces: vec![O, I], next: 0 });
+        let mut board = Board::with_size(pieces, Box::new(Srs::new(HalfKicks::Tetrio)), 4, 10, 2);
+        board.set_mode(Box::new(Marathon::new(1)));
+        board.set_clear_delay(0);
+        board.set_entry_delay(0);
+
+        let mut engine = Engine::new(board);
+        engine.apply(Action::HardDrop);
+        engine.apply(Action::HardDrop);
+
+        // The line reaches the goal straight away, even though the Board hasn't been
+        // advanced to a frame that reports it
+        let board = engine.board_mut();
+        let current = board.current();
+
+        board.left();
+        board.rotate(Direction::Clockwise);
+        board.hold_tetromino();
+        board.drop_tetromino();
+
+        assert_eq!(board.current(), current);
+        assert_eq!(board.hold(), None);
+        assert_eq!(board.pieces(), 2);
+        assert_eq!(engine.advance(1), [Event::Completed]);
+    }
+
+    #[test]
+    fn shifts_reach_the_walls() {
+        let mut engine = Engine::seeded(42);
+        let columns = |engine: &Engine| {
+            let current = engine.board().current();
+            let xs = current.minos().iter().map(|mino| current.origin().x + mino.x).collect::<Vec<_>>();
+            (*xs.iter().min().unwrap(), *xs.iter().max().unwrap())
+        };
+
+        assert!(engine.apply(Action::ShiftLeft).is_empty());
+        assert_eq!(columns(&engine).0, 0);
+
+        let origin = engine.board().current().origin();
+        engine.apply(Action::MoveLeft);
+        assert_eq!(engine.board().current().origin(), origin);
+
+        engine.apply(Action::ShiftRight);
+        assert_eq!(columns(&engine).1, engine.board().width() as isize - 1);
+    }
+
+    #[test]
+    fn t_spin_double() {
+        let pieces = Box::new(Sequence { pieces: vec![J, J, T, T, O], next: 0 });
+        let board = Board::with_size(pieces, Box::new(Srs::new(HalfKicks::Tetrio)), 4, 10, 2);
+        let mut engine = Engine::new(board);
+
+        // Build a slot with an overhang on a 4 wide field:
+        //
+        //   ..T.
+        //   ..TT
+        //   ..TJ
+        //   ...J
+        //   J.JJ
+        //   JJJ.
+        engine.apply(Action::HardDrop);
+
+        engine.apply(Action::RotateCounterClockwise);
+        engine.apply(Action::ShiftRight);
+        engine.apply(Action::HardDrop);
+
+        engine.apply(Action::RotateClockwise);
+        engine.apply(Action::ShiftRight);
+        engine.apply(Action::HardDrop);
+
+        // Drop the T beside the slot on its side, then kick it in with a final rotation
+        engine.apply(Action::RotateClockwise);
+        engine.apply(Action::ShiftLeft);
+        engine.apply(Action::SonicDrop);
+        assert!(engine.apply(Action::RotateClockwise).is_empty());
+
+        let events = engine.apply(Action::HardDrop).to_vec();
+        assert_eq!(events.len(), 2);
+        assert!(matches!(events[0], Event::Locked(tetromino, Spin::Full) if tetromino.tetromino_type() == T));
+        assert!(matches!(events[1], Event::Cleared(clear) if clear.spin == Spin::Full && clear.lines == 2));
+    }
+
+    /// Sonic drops the current Tetromino and waits for it to lock. Returns the points
+    /// scored by the lock alone, without those for dropping it
+    fn lock_points(engine: &mut Engine) -> usize {
+        engine.apply(Action::SonicDrop);
+        let score = engine.board().score();
+        assert!(matches!(engine.advance(30).first(), Some(Event::Locked(..))));
+        engine.board().score() - score
+    }
+
+    #[test]
+    fn scores_at_the_level_before_the_clear() {
+        let mut pieces = vec![O];
+        pieces.extend(vec![I; 11]);
+
+        let board = Board::with_size(Box::new(Sequence { pieces: pieces, next: 0 }), Box::new(Srs::new(HalfKicks::Tetrio)), 4, 10, 2);
+        let mut engine = Engine::new(board);
+
+        // An O underneath keeps every single from being a perfect clear
+        assert_eq!(lock_points(&mut engine), 0);
+
+        for combo in 0..10 {
+            assert_eq!(lock_points(&mut engine), 100 + 50 * combo);
+        }
+
+        // The tenth single levelled up, so only the eleventh is worth double
+        assert_eq!(engine.board().level(), 1);
+        assert_eq!(lock_points(&mut engine), (100 + 50 * 10) * 2);
+    }
+
+    #[test]
+    fn back_to_back_and_combo() {
+        let pieces = Box::new(Sequence { pieces: vec![J, J, T, T, I, T, I, I], next: 0 });
+        let board = Board::with_size(pieces, Box::new(Srs::new(HalfKicks::Tetrio)), 4, 10, 2);
+        let mut engine = Engine::new(board);
+
+        // Set up the same T-spin double as above, which starts a back-to-back chain
+        engine.apply(Action::HardDrop);
+
+        engine.apply(Action::RotateCounterClockwise);
+        engine.apply(Action::ShiftRight);
+        engine.apply(Action::HardDrop);
+
+        engine.apply(Action::RotateClockwise);
+        engine.apply(Action::ShiftRight);
+        engine.apply(Action::HardDrop);
+
+        engine.apply(Action::RotateClockwise);
+        engine.apply(Action::ShiftLeft);
+        engine.apply(Action::SonicDrop);
+        engine.apply(Action::RotateClockwise);
+
+        assert_eq!(lock_points(&mut engine), 1200);
+        assert_eq!(engine.board().last_clear(), Some(Clear { lines: 2, spin: Spin::Full, back_to_back: false, perfect_clear: false }));
+        assert_eq!(engine.board().combo(), Some(0));
+        assert!(engine.board().is_back_to_back());
+
+        // Locking without clearing a line ends the combo, but not the chain
+        engine.apply(Action::RotateClockwise);
+        engine.apply(Action::ShiftLeft);
+
+        assert_eq!(lock_points(&mut engine), 0);
+        assert_eq!(engine.board().combo(), None);
+        assert!(engine.board().is_back_to_back());
+
+        // Neither does a T-spin that clears no lines, which scores without a bonus
+        engine.apply(Action::MoveRight);
+        engine.apply(Action::SonicDrop);
+        engine.apply(Action::RotateCounterClockwise);
+
+        assert_eq!(lock_points(&mut engine), 100);
+        assert_eq!(engine.board().last_clear(), Some(Clear { lines: 0, spin: Spin::Mini, back_to_back: false, perfect_clear: false }));
+        assert!(engine.board().is_back_to_back());
+
+        // A tetris continues the chain for one and a half times the points
+        engine.apply(Action::RotateClockwise);
+        engine.apply(Action::MoveLeft);
+
+        assert_eq!(lock_points(&mut engine), 1200);
+        assert_eq!(engine.board().last_clear(), Some(Clear { lines: 4, spin: Spin::None, back_to_back: true, perfect_clear: false }));
+        assert_eq!(engine.board().combo(), Some(0));
+
+        // A single straight after adds to the combo, but breaks the chain
+        assert_eq!(lock_points(&mut engine), 100 + 50);
+        assert_eq!(engine.board().last_clear(), Some(Clear { lines: 1, spin: Spin::None, back_to_back: false, perfect_clear: false }));
+        assert_eq!(engine.board().combo(), Some(1));
+        assert!(!engine.board().is_back_to_back());
+    }
+
+    #[test]
+    fn perfect_clears() {
+        let pieces = Box::new(Sequence { pieces: vec![I], next: 0 });
+        let board = Board::with_size(pieces, Box::new(Srs::new(HalfKicks::Tetrio)), 4, 10, 2);
+        let mut engine = Engine::new(board);
+
+        // Stands four I Tetrominos side by side, clearing the whole field with a tetris
+        let tetris = |engine: &mut Engine| {
+            engine.apply(Action::RotateClockwise);
+            engine.apply(Action::ShiftLeft);
+            assert_eq!(lock_points(engine), 0);
+
+            engine.apply(Action::RotateCounterClockwise);
+            assert_eq!(lock_points(engine), 0);
+
+            engine.apply(Action::RotateClockwise);
+            assert_eq!(lock_points(engine), 0);
+
+            engine.apply(Action::RotateClockwise);
+            engine.apply(Action::ShiftRight);
+            lock_points(engine)
+        };
+
+        assert_eq!(tetris(&mut engine), 800 + 2000);
+        assert_eq!(engine.board().last_clear(), Some(Clear { lines: 4, spin: Spin::None, back_to_back: false, perfect_clear: true }));
+
+        // A back-to-back tetris gets the bigger bonus on top of its own
+        assert_eq!(tetris(&mut engine), 800 * 3 / 2 + 3200);
+        assert_eq!(engine.board().last_clear(), Some(Clear { lines: 4, spin: Spin::None, back_to_back: true, perfect_clear: true }));
+
+        // A single laid flat on an empty field clears it again, continuing the combo
+        assert_eq!(lock_points(&mut engine), 100 + 800 + 50);
+        assert_eq!(engine.board().last_clear(), Some(Clear { lines: 1, spin: Spin::None, back_to_back: false, perfect_clear: true }));
+        assert_eq!(engine.board().perfect_clears(), 3);
+    }
+
+    /// Gets an Engine whose first Tetromino has been sonic dropped onto the floor, with
+    /// its lock delay reset in a specified way
+    fn grounded(lock_reset: LockReset) -> Engine {
+        let mut engine = Engine::seeded(42);
+        engine.board_mut().set_lock_reset(lock_reset);
+        engine.apply(Action::SonicDrop);
+        engine
+    }
+
+    /// Advances an Engine a frame at a time until its current Tetromino locks. Returns
+    /// the number of frames that it took
+    fn frames_until_lock(engine: &mut Engine) -> usize {
+        for frames in 1..=1000 {
+            if engine.advance(1).iter().any(|event| matches!(event, Event::Locked(..))) {
+                return frames;
+            }
+        }
+
+        panic!("the Tetromino never locked");
+    }
+
+    /// Waits out all but the last frame of the lock delay a number of times, moving the
+    /// current Tetromino back and forth in between without letting it lock
+    fn stall(engine: &mut Engine, times: usize) {
+        for i in 0..times {
+            assert!(engine.advance(29).is_empty());
+            assert!(engine.apply(if i % 2 == 0 { Action::MoveLeft } else { Action::MoveRight }).is_empty());
+        }
+    }
+
+    #[test]
+    fn lock_delay_expires() {
+        let mut engine = grounded(LockReset::Move(15));
+        assert_eq!(frames_until_lock(&mut engine), 30);
+    }
+
+    #[test]
+    fn step_reset_ignores_moves() {
+        let mut engine = grounded(LockReset::Step);
+        assert!(engine.advance(20).is_empty());
+        assert!(engine.apply(Action::MoveLeft).is_empty());
+        assert_eq!(frames_until_lock(&mut engine), 10);
+    }
+
+    #[test]
+    fn infinity_reset_never_runs_out() {
+        let mut engine = grounded(LockReset::Infinity);
+        stall(&mut engine, 50);
+        assert_eq!(frames_until_lock(&mut engine), 30);
+    }
+
+    #[test]
+    fn move_reset_runs_out() {
+        let mut engine = grounded(LockReset::Move(15));
+        stall(&mut engine, 14);
+        assert_eq!(frames_until_lock(&mut engine), 30);
+
+        // The last reset is used up on the frame after it is taken
+        let mut engine = grounded(LockReset::Move(15));
+        stall(&mut engine, 15);
+        assert_eq!(frames_until_lock(&mut engine), 1);
+    }
+}
@@ -1,125 +1,835 @@
-extern crate rustbox;
-
-use std::error::Error;
-use std::time::Duration;
-use std::sync::mpsc;
 use std::thread;
+use std::time::{Duration, Instant};
 
-use std::sync::{Arc, Mutex};
-
-use self::rustbox::RustBox;
-use self::rustbox::Key;
+use yatc_rs::board::{Board, FPS, PREVIEWS, SKYLINE};
+use yatc_rs::mode;
+use yatc_rs::randomizer;
+use yatc_rs::rotation;
+use yatc_rs::rotation::Direction;
+use yatc_rs::srs::HalfKicks;
 
+use super::Options;
+use super::bindings::{Action, Bindings};
+use super::handling::{Controller, Repeat};
+use super::input::{Input, Key, KeyEvent};
+use super::menu::{Choice, Menu};
+use super::replay::Replay;
+use super::scores::{self, Entry, HighScores, NAME_LENGTH};
+use super::surface::Surface;
 use super::ui::Ui;
-use super::board::Board;
-use super::srs::Direction;
 
-const TIMEOUT: u64 = 100;
-const FPS: u64 = 60;
-const DELAY: [u64; 20] = [ 800, 720, 630, 550, 470, 
-                           380, 300, 220, 130, 100, 
-                            80,  80,  80,  70,  70, 
-                            70,  50,  50,  50,  30, ];
+// The largest handling times that can be chosen in the options menu, in frames
+const MAX_DAS: usize = 60;
+const MAX_ARR: usize = 30;
+
+// The fastest that a replay can be played back, as a multiple of its normal speed
+const MAX_SPEED: usize = 16;
+
+// The number of frames that a replay is rewound by at a time
+const REWIND_FRAMES: usize = 5 * FPS as usize;
+
+// The settings that can be changed in the options menu, in the order they are listed
+const OPTIONS: [&str; 7] = ["ROTATION", "RANDOMIZER", "PREVIEWS", "DAS", "ARR", "IRS", "IHS"];
+
+/// A screen that the player can be on
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum Screen {
+    Title,
+    Modes,
+    Options,
+    HighScores,
+    Play,
+    GameOver,
+    Quit,
+}
 
 /// A controller between the terminal view and game state
 pub struct Game<'a> {
-    rb: &'a RustBox,
+    surface: &'a dyn Surface,
+    input: &'a dyn Input,
     ui: Ui<'a>,
-    board: Arc<Mutex<Board>>,
+    board: Board,
+    controller: Controller,
+    bindings: Bindings,
+    options: Options,
+    scores: HighScores,
+    replay: Replay,
+    seed: u64,
+    has_release: bool,
+    pause: Menu,
+    is_paused: bool,
 }
 
 impl<'a> Game<'a> {
 
-    /// Initializes a new Game struct
-    pub fn new(rb: &'a RustBox) -> Self {
+    /// Initializes a new Game struct drawn onto a Surface and controlled by an Input,
+    /// using the settings chosen by the player and recording their high scores
+    pub fn new(surface: &'a dyn Surface, input: &'a dyn Input, bindings: Bindings, options: Options, scores: HighScores) -> Self {
+        let seed = options.seed.unwrap_or_else(randomizer::random_seed);
+        let pause = vec!["RESUME", "RETRY", "END GAME", "MAIN MENU"];
+
         Game {
-            rb: rb,
-            ui: Ui::new(rb),
-            board: Arc::new(Mutex::new(Board::new())),
+            surface: surface,
+            input: input,
+            ui: Ui::new(surface, options.previews, options.width, options.height),
+            board: Game::new_board(&options, seed),
+            controller: Controller::new(options.handling, input.has_release()),
+            bindings: bindings,
+            replay: Replay::new(&options, seed, input.has_release()),
+            options: options,
+            scores: scores,
+            seed: seed,
+            has_release: input.has_release(),
+            pause: Menu::new("PAUSED", pause.into_iter().map(String::from).collect()),
+            is_paused: false,
         }
     }
 
-    /// Starts the main game loop
+    /// Initializes a new Board whose pieces are drawn from the chosen randomizer
+    /// and rotated by the chosen rotation system, played in the chosen mode
+    fn new_board(options: &Options, seed: u64) -> Board {
+        let randomizer = randomizer::from_name(&options.randomizer, seed)
+            .expect("randomizer names are validated when the options are parsed");
+
+        let rotation = rotation::from_name(&options.rotation, options.half_kicks)
+            .expect("rotation system names are validated when the options are parsed");
+
+        let mode = mode::from_name(&options.mode)
+            .expect("mode names are validated when the options are parsed");
+
+        let height = options.height + options.buffer;
+        let mut board = Board::with_size(randomizer, rotation, options.width, height, options.buffer);
+        board.set_mode(mode);
+        board.set_irs(options.irs);
+        board.set_ihs(options.ihs);
+        board.set_entry_delay(options.entry_delay);
+        board.set_clear_delay(options.clear_delay);
+        board.set_partial_lock_out(options.partial_lock_out);
+        board
+    }
+
+    /// Starts the game on the title menu, moving between screens until the player quits
     pub fn run(&mut self) {
-        self.ui.setup();
+        let mut screen = Screen::Title;
 
-        // Create a channel to communicate from the main thread to 
-        // the gravity thread about whether the game has ended
-        let (send, recv) = mpsc::channel();
+        while screen != Screen::Quit {
+            screen = match screen {
+                Screen::Title      => self.title(),
+                Screen::Modes      => self.modes(),
+                Screen::Options    => self.options(),
+                Screen::HighScores => self.high_scores(),
+                Screen::Play       => self.play(),
+                Screen::GameOver   => self.game_over(),
+                Screen::Quit       => Screen::Quit,
+            };
+        }
+    }
 
-        // Spawn a thread that periodically ticks the board
-        let guard = self.board.clone();
-        let gravity = thread::spawn(move || {
-            let mut delay = DELAY[0];
+    /// Shows the title menu. Returns the screen picked by the player
+    fn title(&mut self) -> Screen {
+        let mut menu = Game::menu("YATC-RS", &["PLAY", "OPTIONS", "HIGH SCORES", "QUIT"]);
 
-            loop {
+        match self.select(&mut menu, &|menu| self.ui.print_menu(menu)) {
+            Choice::Pick(0) => Screen::Modes,
+            Choice::Pick(1) => Screen::Options,
+            Choice::Pick(2) => Screen::HighScores,
+            Choice::Pick(_) | Choice::Back => Screen::Quit,
+            Choice::Adjust(_, _) => Screen::Title,
+        }
+    }
 
-                // Check for a message from the main thread and handle it
-                if let Ok(true) = recv.try_recv() {
-                    break;
+    /// Shows the list of modes, starting a game in the one picked by the player
+    fn modes(&mut self) -> Screen {
+        let names: Vec<String> = mode::NAMES.iter().map(|name| name.to_uppercase()).collect();
+        let mut menu = Menu::new("MODE", names);
+        menu.set_selected(mode::NAMES.iter().position(|&name| name == self.options.mode).unwrap_or(0));
+
+        loop {
+            match self.select(&mut menu, &|menu| self.ui.print_menu(menu)) {
+                Choice::Pick(i) => {
+                    self.options.mode = String::from(mode::NAMES[i]);
+                    return self.start();
+                },
+
+                Choice::Back => return Screen::Title,
+                Choice::Adjust(_, _) => { },
+            }
+        }
+    }
+
+    /// Shows the settings that can be changed between games. Left and right or Enter
+    /// steps through the values of a setting
+    fn options(&mut self) -> Screen {
+        let mut menu = Menu::new("OPTIONS", self.option_items());
+
+        loop {
+            match self.select(&mut menu, &|menu| self.ui.print_menu(menu)) {
+                Choice::Adjust(i, step) => self.adjust(i, step),
+                Choice::Pick(i) if i < OPTIONS.len() => self.adjust(i, 1),
+                Choice::Pick(_) | Choice::Back => break,
+            }
+
+            menu.set_items(self.option_items());
+        }
+
+        // The layout depends on the number of previews
+        self.ui = Ui::new(self.surface, self.options.previews, self.options.width, self.options.height);
+        Screen::Title
+    }
+
+    /// Gets the items of the options menu, showing the current value of each setting
+    fn option_items(&self) -> Vec<String> {
+        let options = &self.options;
+        let on_off = |is_on| if is_on { "ON" } else { "OFF" };
+
+        let values = [
+            options.rotation.to_uppercase(),
+            options.randomizer.to_uppercase(),
+            options.previews.to_string(),
+            format!("{}F", options.handling.das),
+            format!("{}F", options.handling.arr),
+            String::from(on_off(options.irs)),
+            String::from(on_off(options.ihs)),
+        ];
+
+        let mut items: Vec<String> = OPTIONS.iter()
+            .zip(values.iter())
+            .map(|(label, value)| format!("{:<11}{:>8}", label, value))
+            .collect();
+
+        items.push(String::from("BACK"));
+        items
+    }
+
+    /// Steps the value of a setting in the options menu forwards or backwards
+    fn adjust(&mut self, item: usize, step: isize) {
+        let options = &mut self.options;
+
+        match OPTIONS.get(item) {
+            Some(&"ROTATION")   => options.rotation = Game::cycle(&rotation::NAMES, &options.rotation, step),
+            Some(&"RANDOMIZER") => options.randomizer = Game::cycle(&randomizer::NAMES, &options.randomizer, step),
+            Some(&"PREVIEWS")   => options.previews = Game::step(options.previews, step, PREVIEWS),
+            Some(&"DAS")        => options.handling.das = Game::step(options.handling.das, step, MAX_DAS),
+            Some(&"ARR")        => options.handling.arr = Game::step(options.handling.arr, step, MAX_ARR),
+            Some(&"IRS")        => options.irs = !options.irs,
+            Some(&"IHS")        => options.ihs = !options.ihs,
+            _ => { },
+        }
+    }
+
+    /// Gets the name a number of steps away from another in a list, wrapping around its ends
+    fn cycle(names: &[&str], name: &str, step: isize) -> String {
+        let i = names.iter().position(|&n| n == name).unwrap_or(0) as isize;
+        String::from(names[(i + step).rem_euclid(names.len() as isize) as usize])
+    }
+
+    /// Steps a value, keeping it between zero and a maximum
+    fn step(value: usize, step: isize, max: usize) -> usize {
+        (value as isize + step).clamp(0, max as isize) as usize
+    }
+
+    /// Shows the high score tables for the current rules, starting with the current
+    /// mode. Left and right switch between modes
+    fn high_scores(&mut self) -> Screen {
+        let mut i = mode::NAMES.iter().position(|&name| name == self.options.mode).unwrap_or(0);
+
+        loop {
+            let name = mode::NAMES[i];
+            self.ui.print_high_scores(&name.to_uppercase(), &self.rules(), self.scores.table(&self.table_name(name)));
+            self.ui.present();
+
+            match self.next_key() {
+                Key::Left  => i = (i + mode::NAMES.len() - 1) % mode::NAMES.len(),
+                Key::Right => i = (i + 1) % mode::NAMES.len(),
+                Key::Enter | Key::Esc => return Screen::Title,
+                _ => { },
+            }
+        }
+    }
+
+    /// Gets a description of the rules that games are being played by. Rules that are
+    /// rarely changed are only listed when they differ from their defaults
+    fn rules(&self) -> String {
+        let options = &self.options;
+
+        let mut rules = vec![
+            options.rotation.clone(),
+            options.randomizer.clone(),
+            format!("{}x{}", options.width, options.height),
+            format!("next-{}", options.previews),
+        ];
+
+        if options.buffer != SKYLINE {
+            rules.push(format!("buffer-{}", options.buffer));
+        }
+
+        if options.half_kicks == HalfKicks::None {
+            rules.push(String::from("no-half-kicks"));
+        }
+
+        if !options.irs {
+            rules.push(String::from("no-irs"));
+        }
+
+        if !options.ihs {
+            rules.push(String::from("no-ihs"));
+        }
+
+        if options.entry_delay > 0 {
+            rules.push(format!("are-{}f", options.entry_delay));
+        }
+
+        if options.clear_delay > 0 {
+            rules.push(format!("lcd-{}f", options.clear_delay));
+        }
+
+        if options.partial_lock_out {
+            rules.push(String::from("partial-lock-out"));
+        }
+
+        rules.join(" ")
+    }
+
+    /// Gets the name of the high score table for a mode played by the current rules,
+    /// so that only games played the same way are ranked against each other
+    fn table_name(&self, mode: &str) -> String {
+        format!("{} {}", mode, self.rules())
+    }
+
+    /// Shows the results of the last game until the player retries or returns to
+    /// the title menu, after recording it if it made its high score table. The key
+    /// bound to restarting also retries, and the game's replay can be saved
+    fn game_over(&mut self) -> Screen {
+        let mut menu = Game::menu("", &["RETRY", "SAVE REPLAY", "MAIN MENU"]);
+        self.replay.set_frames(self.board.frames());
+        self.render();
+
+        let is_unsaved = self.record().is_err();
+
+        loop {
+            self.ui.print_game_over(&self.board, self.seed, &menu, is_unsaved);
+            self.ui.present();
+
+            let key = self.next_key();
+
+            if self.bindings.action(key) == Some(Action::Restart) {
+                return self.start();
+            }
+
+            match menu.handle(key) {
+                Some(Choice::Pick(0)) => return self.start(),
+
+                Some(Choice::Pick(1)) => {
+                    let saved = if self.replay.save().is_ok() { "REPLAY SAVED" } else { "REPLAY NOT SAVED" };
+                    menu.set_items(vec![String::from("RETRY"), String::from(saved), String::from("MAIN MENU")]);
+                },
+
+                Some(Choice::Pick(_)) | Some(Choice::Back) => return Screen::Title,
+                _ => { },
+            }
+        }
+    }
+
+    /// Asks for the player's name if the last game made its high score table, then adds it
+    /// to the table and saves the high scores. The name can be skipped with Esc, leaving
+    /// the game unrecorded
+    fn record(&mut self) -> Result<(), String> {
+        let by_time = self.board.mode().ranks_by_time();
+
+        // Games ranked by time only count once the goal is reached, and a game
+        // that didn't score any points isn't worth recording
+        if (by_time && !self.board.is_complete()) || (!by_time && self.board.score() == 0) {
+            return Ok(());
+        }
+
+        let table = self.table_name(&self.options.mode);
+        let mut name = String::from(self.scores.name());
+
+        let rank = match self.scores.rank(&table, &Entry::new(&name, &self.board, self.seed), by_time) {
+            Some(rank) => rank,
+            None => return Ok(()),
+        };
+
+        loop {
+            self.ui.print_name_entry(&self.board, self.seed, rank, &name);
+            self.ui.present();
+
+            match self.next_key() {
+                Key::Char(c) if scores::is_name_char(c) && name.len() < NAME_LENGTH => name.push(c.to_ascii_uppercase()),
+                Key::Backspace => { name.pop(); },
+                Key::Enter if !name.is_empty() => break,
+                Key::Esc => return Ok(()),
+                _ => { },
+            }
+        }
+
+        self.scores.set_name(&name);
+        self.scores.insert(&table, Entry::new(&name, &self.board, self.seed), by_time);
+        self.scores.save()
+    }
+
+    /// Creates a Menu from a list of items
+    fn menu(title: &'static str, items: &[&str]) -> Menu {
+        Menu::new(title, items.iter().map(|&item| String::from(item)).collect())
+    }
+
+    /// Prints a Menu and passes it keys until the player makes a Choice
+    fn select(&self, menu: &mut Menu, print: &dyn Fn(&Menu)) -> Choice {
+        loop {
+            print(menu);
+            self.ui.present();
+
+            if let Some(choice) = menu.handle(self.next_key()) {
+                return choice;
+            }
+        }
+    }
+
+    /// Waits for the next key to be pressed, including presses repeated while it is held
+    fn next_key(&self) -> Key {
+        loop {
+            match self.input.poll_event(None) {
+                Some(KeyEvent::Press(key)) | Some(KeyEvent::Repeat(key)) => return key,
+                _ => { },
+            }
+        }
+    }
+
+    /// Plays frames until the game is over or the player leaves it from the pause menu.
+    /// Returns the next screen
+    fn play(&mut self) -> Screen {
+        let frame = Duration::from_nanos(1_000_000_000 / FPS);
+        let mut deadline = Instant::now();
+
+        loop {
+
+            // The player is leaving the game
+            if let Some(screen) = self.handle_input() {
+                return screen;
+            }
+
+            if self.is_paused {
+                self.ui.print_pause(&self.pause);
+                self.ui.present();
+            }
+
+            else {
+                self.controller.update(&mut self.board);
+                self.board.update();
+
+                // The player has lost or reached the goal of the mode
+                if self.board.is_over() {
+                    return Screen::GameOver;
                 }
 
-                thread::sleep(Duration::from_millis(delay));
+                self.render();
+            }
 
-                let mut board = guard.lock().unwrap();
-                board.tick();
+            // Wait for the next frame. Late frames are never skipped, so that
+            // the game catches up without dropping any frames of input
+            deadline += frame;
 
-                delay = if board.level() <= 19 { DELAY[board.level()] } else { DELAY[19] };
+            let now = Instant::now();
+            if deadline > now {
+                thread::sleep(deadline - now);
             }
-        });
+        }
+    }
+
+    /// Starts a new game on a freshly drawn screen. Returns the screen to play it on
+    fn start(&mut self) -> Screen {
+        self.ui.clear();
+        self.ui.setup();
+        self.restart();
+        Screen::Play
+    }
+
+    /// Starts a new game with the same settings, recording a new replay. A new seed is
+    /// chosen unless the player picked one
+    fn restart(&mut self) {
+        self.seed = self.options.seed.unwrap_or_else(randomizer::random_seed);
+        self.replay = Replay::new(&self.options, self.seed, self.has_release);
+        self.reset();
+        self.is_paused = false;
+    }
+
+    /// Puts the Board and Controller back to how they were at the start of the game
+    fn reset(&mut self) {
+        self.board = Game::new_board(&self.options, self.seed);
+        self.controller = Controller::new(self.options.handling, self.has_release);
+        self.ui.reset();
+    }
+
+    /// Plays back a recorded game until the player quits. Playback can be paused, stepped
+    /// forward a frame at a time while paused, sped up or slowed down and rewound. The
+    /// settings must already match the ones the game was recorded with
+    pub fn watch(&mut self, replay: &Replay) {
+        let frame = Duration::from_nanos(1_000_000_000 / FPS);
+        let mut deadline = Instant::now();
+
+        let mut speed = 1;
+        let mut is_paused = false;
+
+        // The index of the next ActionEvent to apply
+        let mut next = 0;
+
+        self.has_release = replay.has_release();
+        self.seed = replay.seed();
+        self.ui.setup();
+        self.reset();
 
-        // Main thread handles the player input and rendering
         loop {
+            while let Some(event) = self.input.poll_event(Some(Duration::from_millis(0))) {
+                let key = match event {
+                    KeyEvent::Press(key) | KeyEvent::Repeat(key) => key,
+                    KeyEvent::Release(_) => continue,
+                };
+
+                match self.bindings.action(key) {
+                    Some(Action::Quit) => return,
+                    Some(Action::Pause) => is_paused = !is_paused,
 
-            let mut board = self.board.lock().unwrap();
+                    // Play back from the start
+                    Some(Action::Restart) => {
+                        self.reset();
+                        next = 0;
+                    },
 
-            // Handle the player input. Peek at events to avoid blocking
-            match self.rb.peek_event(Duration::from_millis(TIMEOUT), false) {
-                Ok(rustbox::Event::KeyEvent(key)) => {
-                    match key {
-                        Key::Esc => {
-                            // The player is quitting, so inform the gravity thread
-                            send.send(true);
-                            break;
+                    _ => match key {
+                        Key::Up    => speed = (speed * 2).min(MAX_SPEED),
+                        Key::Down  => speed = (speed / 2).max(1),
+                        Key::Right if is_paused => self.advance(replay, &mut next, 1),
+
+                        // The game can't be played backwards, so play it again from the
+                        // start up to the earlier frame
+                        Key::Left => {
+                            let target = self.board.frames().saturating_sub(REWIND_FRAMES);
+                            self.reset();
+                            next = 0;
+                            self.advance(replay, &mut next, target);
                         },
 
-                        Key::Left      => board.left(),
-                        Key::Right     => board.right(),
-                        Key::Down      => board.down(),
-                        Key::Char('z') => board.rotate(Direction::CounterClockwise),
-                        Key::Char('x') => board.rotate(Direction::Clockwise),
-                        Key::Char('c') => board.drop_tetromino(),
-                        Key::Char(' ') => board.hold_tetromino(),
                         _ => { },
+                    },
+                }
+            }
+
+            if !is_paused {
+                self.advance(replay, &mut next, speed);
+            }
+
+            let is_finished = self.board.frames() >= replay.frames() || self.board.is_over();
+
+            self.render();
+            self.ui.print_replay(self.board.frames(), speed, is_paused, is_finished);
+            self.ui.present();
+
+            deadline += frame;
+
+            let now = Instant::now();
+            if deadline > now {
+                thread::sleep(deadline - now);
+            }
+
+            // Don't build up frames to catch up on after a slow rewind
+            else {
+                deadline = now;
+            }
+        }
+    }
+
+    /// Plays a number of frames of a recorded game, applying each ActionEvent before the frame
+    /// it was recorded on. Stops at the end of the game
+    fn advance(&mut self, replay: &Replay, next: &mut usize, frames: usize) {
+        for _ in 0..frames {
+            if self.board.is_over() {
+                return;
+            }
+
+            while let Some(&event) = replay.events().get(*next) {
+                if event.frame > self.board.frames() {
+                    break;
+                }
+
+                if event.is_press {
+                    self.apply_press(event.action);
+                } else {
+                    self.apply_release(event.action);
+                }
+
+                *next += 1;
+            }
+
+            // The inputs on the last frame are still applied, as they may have ended the game
+            if self.board.frames() >= replay.frames() || self.board.is_over() {
+                return;
+            }
+
+            self.controller.update(&mut self.board);
+            self.board.update();
+        }
+    }
+
+    /// Applies all of the player input received since the last frame in the order
+    /// it arrived. While paused, presses go to the pause menu instead of the board.
+    /// Returns the next screen if the player is leaving the game
+    fn handle_input(&mut self) -> Option<Screen> {
+
+        // Poll without a timeout to avoid blocking the frame
+        while let Some(event) = self.input.poll_event(Some(Duration::from_millis(0))) {
+            match event {
+                KeyEvent::Press(key) if self.is_paused => {
+                    if let Some(screen) = self.handle_pause(key) {
+                        return Some(screen);
                     }
                 },
 
-                Err(e) => panic!("{}", e.description()),
+                KeyEvent::Press(key) => {
+                    if let Some(action) = self.bindings.action(key) {
+                        self.press(action);
+                    }
+                },
+
+                KeyEvent::Release(key) => {
+                    if let Some(action) = self.bindings.action(key) {
+                        self.release(action);
+                    }
+                },
 
-                _ => { }
+                // Held keys are repeated by the Controller instead of the terminal
+                KeyEvent::Repeat(_) => { },
             }
+        }
+
+        None
+    }
+
+    /// Handles a key pressed while the pause menu is shown. The key bound to pausing
+    /// resumes the game. Returns the next screen if the player is leaving the game
+    fn handle_pause(&mut self, key: Key) -> Option<Screen> {
+        if self.bindings.action(key) == Some(Action::Pause) {
+            self.is_paused = false;
+            return None;
+        }
+
+        match self.pause.handle(key) {
+            Some(Choice::Pick(0)) | Some(Choice::Back) => self.is_paused = false,
+            Some(Choice::Pick(1)) => return Some(self.start()),
+            Some(Choice::Pick(2)) => return Some(Screen::GameOver),
+            Some(Choice::Pick(_)) => return Some(Screen::Title),
+            _ => { },
+        }
+
+        None
+    }
+
+    /// Performs an Action whose key has been pressed
+    fn press(&mut self, action: Action) {
+        match action {
+            // The game is paused by either key, and unpaused through the pause menu
+            Action::Pause | Action::Quit => {
+                self.is_paused = true;
+                self.pause.set_selected(0);
+                return;
+            },
 
-            // The player has lost, so inform the gravity thread
-            if board.is_topped_out() {
-                send.send(true);
-                break;
+            Action::Restart => {
+                self.restart();
+                return;
+            },
+
+            _ => { },
+        }
+
+        self.replay.record(self.board.frames(), action, true);
+        self.apply_press(action);
+    }
+
+    /// Handles the key of an Action being released
+    fn release(&mut self, action: Action) {
+        self.replay.record(self.board.frames(), action, false);
+        self.apply_release(action);
+    }
+
+    /// Applies an Action that controls the Board being pressed
+    fn apply_press(&mut self, action: Action) {
+        if let Some(input) = Game::repeat(action) {
+            if self.has_release {
+                self.controller.press(input, &mut self.board);
+            } else {
+                self.controller.key(input, &mut self.board);
+            }
+
+            return;
+        }
+
+        // Rotations and holds that stay held are applied to each Tetromino as it spawns,
+        // which needs an Input that can tell when they are let go
+        if let Some(dir) = Game::direction(action) {
+            if self.has_release {
+                self.board.set_initial_rotation(Some(dir));
+            }
+
+            self.board.rotate(dir);
+            return;
+        }
+
+        match action {
+            Action::HardDrop => self.board.drop_tetromino(),
+
+            Action::Hold => {
+                if self.has_release {
+                    self.board.set_initial_hold(true);
+                }
+
+                self.board.hold_tetromino();
+            },
+
+            _ => { },
+        }
+    }
+
+    /// Applies an Action that controls the Board being released
+    fn apply_release(&mut self, action: Action) {
+        if let Some(input) = Game::repeat(action) {
+            self.controller.release(input);
+        }
+
+        else if let Some(dir) = Game::direction(action) {
+            if self.board.initial_rotation() == Some(dir) {
+                self.board.set_initial_rotation(None);
             }
+        }
 
-            self.render(&board);
-            thread::sleep(Duration::from_millis(1000 / FPS));
+        else if action == Action::Hold {
+            self.board.set_initial_hold(false);
         }
+    }
 
-        gravity.join();
+    /// Gets the Direction that an Action rotates in, if any
+    fn direction(action: Action) -> Option<Direction> {
+        match action {
+            Action::RotateClockwise => Some(Direction::Clockwise),
+            Action::RotateCounterClockwise => Some(Direction::CounterClockwise),
+            Action::RotateHalf => Some(Direction::Half),
+            _ => None,
+        }
+    }
+
+    /// Gets the movement input that repeats while an Action's key is held, if any
+    fn repeat(action: Action) -> Option<Repeat> {
+        match action {
+            Action::MoveLeft  => Some(Repeat::Left),
+            Action::MoveRight => Some(Repeat::Right),
+            Action::SoftDrop  => Some(Repeat::SoftDrop),
+            _ => None,
+        }
     }
 
     /// Renders the game state and board to the terminal
-    fn render(&self, board: &Board) {
+    fn render(&self) {
+        let board = &self.board;
+
         self.ui.print_board(board);
-        self.ui.print_next(board.peek_next());
+        self.ui.print_next(&board.peek_next(self.options.previews));
         self.ui.print_hold(board.hold());
-        self.ui.print_score(board.score());
-        self.ui.print_level(board.level());
-        self.ui.print_lines(board.cleared());
-        self.rb.present();
+        self.ui.print_hud(&board.mode().hud(board));
+        self.ui.print_clear(board.last_clear(), board.combo());
+        self.ui.present();
+    }
+ }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use super::super::surface::Buffer;
+
+    // The Actions a scripted game cycles through
+    const SCRIPT: [Action; 12] = [
+        Action::MoveLeft,
+        Action::HardDrop,
+        Action::RotateClockwise,
+        Action::MoveRight,
+        Action::HardDrop,
+        Action::Hold,
+        Action::SoftDrop,
+        Action::RotateCounterClockwise,
+        Action::HardDrop,
+        Action::RotateHalf,
+        Action::MoveLeft,
+        Action::HardDrop,
+    ];
+
+    /// An Input without any key events, for a Game driven by its Actions instead
+    struct Silent {
+        has_release: bool,
+    }
+
+    impl Input for Silent {
+        fn poll_event(&self, _timeout: Option<Duration>) -> Option<KeyEvent> {
+            None
+        }
+
+        fn has_release(&self) -> bool {
+            self.has_release
+        }
+    }
+
+    #[test]
+    fn replays_play_back_the_same_game() {
+        for &has_release in [true, false].iter() {
+            let buffer = Buffer::new(80, 30);
+            let input = Silent { has_release: has_release };
+
+            let options = Options {
+                seed: Some(42),
+                entry_delay: 6,
+                clear_delay: 20,
+                ..Options::default()
+            };
+
+            let mut game = Game::new(&buffer, &input, Bindings::default(), options, HighScores::new());
+
+            // Press an Action every 16 frames and hold it for long enough to charge the DAS
+            for frame in 0..3600 {
+                let action = SCRIPT[(frame / 16) % SCRIPT.len()];
+
+                match frame % 16 {
+                    0 => game.press(action),
+                    12 => game.release(action),
+                    _ => { },
+                }
+
+                game.controller.update(&mut game.board);
+                game.board.update();
+
+                if game.board.is_over() {
+                    break;
+                }
+            }
+
+            game.replay.set_frames(game.board.frames());
+
+            let score = game.board.score();
+            let frames = game.board.frames();
+            let pieces = game.board.pieces();
+            let field = game.board.field().clone();
+            assert!(pieces > 10);
+
+            // Play the game back from its replay file on a fresh Board
+            let replay = Replay::parse(&game.replay.contents()).unwrap();
+            let mut next = 0;
+
+            game.reset();
+            game.advance(&replay, &mut next, frames + 60);
+
+            assert_eq!(next, replay.events().len());
+            assert_eq!(game.board.score(), score);
+            assert_eq!(game.board.frames(), frames);
+            assert_eq!(game.board.pieces(), pieces);
+            assert_eq!(game.board.field(), &field);
+        }
     }
- }
\ No newline at end of file
+}
@@ -0,0 +1,417 @@
+use yatc_rs::board::{Board, FPS};
+
+// Default handling, measured in frames
+const DAS: usize = 10;
+const ARR: usize = 2;
+const SDF: f64 = 6.0;
+
+// Frames without an event before a key is assumed to be released when the terminal
+// cannot report releases. The first gap allows for the terminal's own repeat delay,
+// while the second only needs to cover the time between its repeated events
+const REPEAT_DELAY: usize = 40;
+const REPEAT_GAP: usize = 6;
+
+/// Timing settings for movement inputs that repeat while held
+#[derive(Copy, Clone, Debug)]
+pub struct Handling {
+
+    /// Delayed Auto Shift: frames a direction is held before it starts repeating
+    pub das: usize,
+
+    /// Auto Repeat Rate: frames between each repeated move. Zero moves instantly to the wall
+    pub arr: usize,
+
+    /// Soft Drop Factor: how many times faster than gravity a soft drop falls
+    pub sdf: f64,
+}
+
+impl Default for Handling {
+    fn default() -> Self {
+        Handling {
+            das: DAS,
+            arr: ARR,
+            sdf: SDF,
+        }
+    }
+}
+
+/// Parses a time given in frames ("10f") or milliseconds ("167ms" or "167") into frames
+pub fn parse_frames(s: &str) -> Result<usize, String> {
+    let (value, is_frames) = if let Some(value) = s.strip_suffix("ms") {
+        (value, false)
+    } else if let Some(value) = s.strip_suffix('f') {
+        (value, true)
+    } else {
+        (s, false)
+    };
+
+    match value.parse::<f64>() {
+        Ok(value) if value >= 0.0 && value.is_finite() => {
+            let frames = if is_frames { value } else { value * FPS as f64 / 1000.0 };
+            Ok(frames.round() as usize)
+        },
+
+        _ => Err(format!("Invalid time '{}'", s)),
+    }
+}
+
+/// Parses a soft drop factor, which may be "inf" for an instant soft drop
+pub fn parse_sdf(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(sdf) if sdf >= 1.0 => Ok(sdf),
+        _ => Err(format!("Invalid soft drop factor '{}'", s)),
+    }
+}
+
+/// A movement input that repeats while it is held
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Repeat {
+    Left,
+    Right,
+    SoftDrop,
+}
+
+/// The state of a held movement input
+#[derive(Copy, Clone, Debug)]
+struct Held {
+    frames: usize,
+    idle: usize,
+    timer: usize,
+    is_confirmed: bool,
+    is_shifting: bool,
+}
+
+impl Held {
+
+    /// Initializes a new Held struct for a freshly pressed input
+    fn new(is_confirmed: bool) -> Self {
+        Held {
+            frames: 0,
+            idle: 0,
+            timer: 0,
+            is_confirmed: is_confirmed,
+            is_shifting: false,
+        }
+    }
+}
+
+/// Applies DAS, ARR and SDF to the board for held movement inputs.
+///
+/// When the terminal can report key releases, an input is held from its press
+/// until its release. Otherwise, releases are inferred from the timing of the
+/// terminal's repeated key events, and an input only counts as held once the
+/// terminal has started repeating it
+pub struct Controller {
+    handling: Handling,
+    has_release: bool,
+    left: Option<Held>,
+    right: Option<Held>,
+    soft_drop: Option<Held>,
+    shift: Option<Repeat>,
+    fall: f64,
+}
+
+impl Controller {
+
+    /// Initializes a new Controller struct. has_release states whether the
+    /// terminal reports key release events
+    pub fn new(handling: Handling, has_release: bool) -> Self {
+        Controller {
+            handling: handling,
+            has_release: has_release,
+            left: None,
+            right: None,
+            soft_drop: None,
+            shift: None,
+            fall: 0.0,
+        }
+    }
+
+    /// Handles a key event for an input from a terminal without release events
+    pub fn key(&mut self, input: Repeat, board: &mut Board) {
+        let repeated = match *self.held(input) {
+
+            // Events in quick succession are the terminal repeating a held key
+            Some(ref mut held) if held.idle <= REPEAT_GAP => {
+                held.idle = 0;
+                held.is_confirmed = true;
+                true
+            },
+
+            // Events after a longer pause are either another tap or the first
+            // repeat, so perform a single step but keep charging the DAS
+            Some(ref mut held) if !held.is_confirmed && held.idle <= REPEAT_DELAY => {
+                held.idle = 0;
+                false
+            },
+
+            _ => {
+                self.press(input, board);
+                return;
+            },
+        };
+
+        if !repeated {
+            Controller::step(input, board);
+        }
+    }
+
+    /// Handles an input being pressed down
+    pub fn press(&mut self, input: Repeat, board: &mut Board) {
+        let is_confirmed = self.has_release;
+
+        if self.held(input).is_some() && self.has_release {
+            return;
+        }
+
+        *self.held(input) = Some(Held::new(is_confirmed));
+
+        // Only a new soft drop starts falling afresh, so pressing a direction
+        // partway through one doesn't hold it up
+        if input == Repeat::SoftDrop {
+            self.fall = 0.0;
+        } else {
+            self.shift = Some(input);
+        }
+
+        Controller::step(input, board);
+    }
+
+    /// Handles an input being released
+    pub fn release(&mut self, input: Repeat) {
+        *self.held(input) = None;
+
+        if self.shift == Some(input) {
+            self.shift = match input {
+                Repeat::Left if self.right.is_some() => Some(Repeat::Right),
+                Repeat::Right if self.left.is_some() => Some(Repeat::Left),
+                _ => None,
+            };
+        }
+    }
+
+    /// Applies a frame of the held inputs to the board
+    pub fn update(&mut self, board: &mut Board) {
+        let has_release = self.has_release;
+
+        // Age the held inputs and infer any releases
+        for &input in [Repeat::Left, Repeat::Right, Repeat::SoftDrop].iter() {
+            let released = match *self.held(input) {
+                Some(ref mut held) => {
+                    held.frames += 1;
+                    held.idle += 1;
+
+                    let timeout = if held.is_confirmed { REPEAT_GAP } else { REPEAT_DELAY };
+                    !has_release && held.idle > timeout
+                },
+
+                None => false,
+            };
+
+            if released {
+                self.release(input);
+            }
+        }
+
+        if let Some(shift) = self.shift {
+            self.update_shift(shift, board);
+        }
+
+        self.update_soft_drop(board);
+    }
+
+    /// Applies DAS and ARR to the held horizontal direction
+    fn update_shift(&mut self, shift: Repeat, board: &mut Board) {
+        let handling = self.handling;
+        let width = board.width();
+
+        let steps = match *self.held(shift) {
+            Some(ref mut held) if held.is_confirmed && held.frames >= handling.das => {
+
+                // The DAS has just charged, so shift once straight away
+                if !held.is_shifting {
+                    held.is_shifting = true;
+                    held.timer = 0;
+                    if handling.arr == 0 { width } else { 1 }
+                }
+
+                else if handling.arr == 0 {
+                    width
+                }
+
+                else {
+                    held.timer += 1;
+
+                    if held.timer >= handling.arr {
+                        held.timer = 0;
+                        1
+                    } else {
+                        0
+                    }
+                }
+            },
+
+            _ => 0,
+        };
+
+        for _ in 0..steps {
+            Controller::step(shift, board);
+        }
+    }
+
+    /// Applies SDF to a held soft drop
+    fn update_soft_drop(&mut self, board: &mut Board) {
+        match self.soft_drop {
+            Some(held) if held.is_confirmed => { },
+            _ => return,
+        }
+
+        // An infinite soft drop factor drops straight to the stack
+        if self.handling.sdf.is_infinite() {
+            for _ in 0..board.height() {
+                board.down();
+            }
+
+            return;
+        }
+
+        self.fall += board.gravity() * self.handling.sdf;
+
+        while self.fall >= 1.0 {
+            board.down();
+            self.fall -= 1.0;
+        }
+    }
+
+    /// Gets the held state of an input
+    fn held(&mut self, input: Repeat) -> &mut Option<Held> {
+        match input {
+            Repeat::Left => &mut self.left,
+            Repeat::Right => &mut self.right,
+            Repeat::SoftDrop => &mut self.soft_drop,
+        }
+    }
+
+    /// Moves the current Tetromino a single step for an input
+    fn step(input: Repeat, board: &mut Board) {
+        match input {
+            Repeat::Left => board.left(),
+            Repeat::Right => board.right(),
+            Repeat::SoftDrop => board.down(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yatc_rs::randomizer::Bag;
+    use yatc_rs::srs::{HalfKicks, Srs};
+
+    /// Gets a standard Board whose first Tetromino is a T
+    fn board() -> Board {
+        Board::new(Box::new(Bag::new(1, 42)), Box::new(Srs::new(HalfKicks::Tetrio)))
+    }
+
+    /// Gets a Controller with the default handling apart from some changes
+    fn controller(arr: usize, sdf: f64, has_release: bool) -> Controller {
+        Controller::new(Handling { das: DAS, arr: arr, sdf: sdf }, has_release)
+    }
+
+    /// Applies a number of frames of the held inputs to the board
+    fn update(controller: &mut Controller, board: &mut Board, frames: usize) {
+        for _ in 0..frames {
+            controller.update(board);
+        }
+    }
+
+    #[test]
+    fn das_charges_before_repeating() {
+        let mut board = board();
+        let mut controller = controller(ARR, SDF, true);
+        let x = board.current().origin().x;
+
+        controller.press(Repeat::Left, &mut board);
+        assert_eq!(board.current().origin().x, x - 1);
+
+        update(&mut controller, &mut board, DAS - 1);
+        assert_eq!(board.current().origin().x, x - 1);
+
+        update(&mut controller, &mut board, 1);
+        assert_eq!(board.current().origin().x, x - 2);
+
+        update(&mut controller, &mut board, ARR);
+        assert_eq!(board.current().origin().x, x - 3);
+
+        controller.release(Repeat::Left);
+        update(&mut controller, &mut board, DAS);
+        assert_eq!(board.current().origin().x, x - 3);
+    }
+
+    #[test]
+    fn zero_arr_is_instant() {
+        let mut board = board();
+        let mut controller = controller(0, SDF, true);
+
+        controller.press(Repeat::Right, &mut board);
+        update(&mut controller, &mut board, DAS);
+
+        let current = board.current();
+        let right = current.minos().iter().map(|mino| current.origin().x + mino.x).max();
+        assert_eq!(right, Some(board.width() as isize - 1));
+    }
+
+    #[test]
+    fn infinite_sdf_reaches_the_stack() {
+        let mut board = board();
+        let mut controller = controller(ARR, f64::INFINITY, true);
+        let y = board.current().origin().y;
+
+        controller.press(Repeat::SoftDrop, &mut board);
+        assert_eq!(board.current().origin().y, y + 1);
+
+        update(&mut controller, &mut board, 1);
+        assert_eq!(board.current().origin(), board.ghost().origin());
+    }
+
+    #[test]
+    fn shifting_keeps_the_soft_drop_going() {
+        let mut board = board();
+        let mut controller = controller(ARR, SDF, true);
+        let y = board.current().origin().y;
+
+        // At level 0, a soft drop falls a row every 8 frames
+        controller.press(Repeat::SoftDrop, &mut board);
+        update(&mut controller, &mut board, 4);
+
+        controller.press(Repeat::Left, &mut board);
+        update(&mut controller, &mut board, 4);
+        assert_eq!(board.current().origin().y, y + 2);
+    }
+
+    #[test]
+    fn infers_releases() {
+        let mut board = board();
+        let mut controller = controller(ARR, SDF, false);
+        let x = board.current().origin().x;
+
+        // A single event might be a tap, so it waits out the terminal's repeat delay
+        controller.key(Repeat::Left, &mut board);
+        update(&mut controller, &mut board, REPEAT_DELAY);
+        assert!(controller.left.is_some());
+        assert_eq!(board.current().origin().x, x - 1);
+
+        update(&mut controller, &mut board, 1);
+        assert!(controller.left.is_none());
+
+        // Once the terminal is repeating the key, it is held until the events stop
+        controller.key(Repeat::Left, &mut board);
+        controller.key(Repeat::Left, &mut board);
+        update(&mut controller, &mut board, REPEAT_GAP);
+        assert!(controller.left.is_some());
+
+        update(&mut controller, &mut board, 1);
+        assert!(controller.left.is_none());
+        assert_eq!(board.current().origin().x, x - 2);
+    }
+}
@@ -0,0 +1,144 @@
+#[cfg(feature = "rustbox")]
+extern crate rustbox;
+
+#[cfg(feature = "crossterm")]
+extern crate crossterm;
+
+use std::time::Duration;
+
+#[cfg(feature = "rustbox")]
+use self::rustbox::RustBox;
+
+#[cfg(feature = "crossterm")]
+use self::crossterm::event;
+
+#[cfg(feature = "crossterm")]
+use super::surface::Crossterm;
+
+/// The keys that can be reported by an Input
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Key {
+    Left,
+    Right,
+    Up,
+    Down,
+    Enter,
+    Esc,
+    Backspace,
+    Shift,
+    Char(char),
+}
+
+/// A change in the state of a Key
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum KeyEvent {
+    Press(Key),
+
+    // Only crossterm can tell repeats and releases apart from presses
+    #[cfg_attr(not(feature = "crossterm"), allow(dead_code))]
+    Repeat(Key),
+    #[cfg_attr(not(feature = "crossterm"), allow(dead_code))]
+    Release(Key),
+}
+
+/// A source of key events, such as a terminal.
+///
+/// Inputs that cannot tell when a key is released report every event as a
+/// press, including those repeated by the terminal while a key is held
+pub trait Input {
+
+    /// Gets the next key event, waiting up to a timeout or indefinitely if there
+    /// is no timeout. Returns None if no key event arrived in time
+    fn poll_event(&self, timeout: Option<Duration>) -> Option<KeyEvent>;
+
+    /// Determines if the Input reports repeat and release events
+    fn has_release(&self) -> bool;
+}
+
+#[cfg(feature = "rustbox")]
+impl Input for RustBox {
+    fn poll_event(&self, timeout: Option<Duration>) -> Option<KeyEvent> {
+        let event = match timeout {
+            Some(timeout) => self.peek_event(timeout, false),
+            None => RustBox::poll_event(self, false),
+        };
+
+        match event {
+            Ok(rustbox::Event::KeyEvent(key)) => rustbox_key(key).map(KeyEvent::Press),
+            Ok(_) => None,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    fn has_release(&self) -> bool {
+        false
+    }
+}
+
+/// Converts a rustbox key into a Key
+#[cfg(feature = "rustbox")]
+fn rustbox_key(key: rustbox::Key) -> Option<Key> {
+    match key {
+        rustbox::Key::Left      => Some(Key::Left),
+        rustbox::Key::Right     => Some(Key::Right),
+        rustbox::Key::Up        => Some(Key::Up),
+        rustbox::Key::Down      => Some(Key::Down),
+        rustbox::Key::Enter     => Some(Key::Enter),
+        rustbox::Key::Esc       => Some(Key::Esc),
+        rustbox::Key::Backspace => Some(Key::Backspace),
+        rustbox::Key::Char(c)   => Some(Key::Char(c)),
+        _ => None,
+    }
+}
+
+#[cfg(feature = "crossterm")]
+impl Input for Crossterm {
+    fn poll_event(&self, timeout: Option<Duration>) -> Option<KeyEvent> {
+        if let Some(timeout) = timeout {
+            match event::poll(timeout) {
+                Ok(true) => { },
+                Ok(false) => return None,
+                Err(e) => panic!("{}", e),
+            }
+        }
+
+        match event::read() {
+            Ok(event::Event::Key(key)) => {
+                let code = crossterm_key(key.code)?;
+
+                match key.kind {
+                    event::KeyEventKind::Press   => Some(KeyEvent::Press(code)),
+                    event::KeyEventKind::Repeat  => Some(KeyEvent::Repeat(code)),
+                    event::KeyEventKind::Release => Some(KeyEvent::Release(code)),
+                }
+            },
+
+            Ok(_) => None,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    fn has_release(&self) -> bool {
+        self.is_enhanced()
+    }
+}
+
+/// Converts a crossterm key code into a Key
+#[cfg(feature = "crossterm")]
+fn crossterm_key(code: event::KeyCode) -> Option<Key> {
+    match code {
+        event::KeyCode::Left      => Some(Key::Left),
+        event::KeyCode::Right     => Some(Key::Right),
+        event::KeyCode::Up        => Some(Key::Up),
+        event::KeyCode::Down      => Some(Key::Down),
+        event::KeyCode::Enter     => Some(Key::Enter),
+        event::KeyCode::Esc       => Some(Key::Esc),
+        event::KeyCode::Backspace => Some(Key::Backspace),
+        event::KeyCode::Char(c)   => Some(Key::Char(c)),
+
+        event::KeyCode::Modifier(event::ModifierKeyCode::LeftShift)
+            | event::KeyCode::Modifier(event::ModifierKeyCode::RightShift) => Some(Key::Shift),
+
+        _ => None,
+    }
+}
@@ -0,0 +1,19 @@
+//! The game engine behind yatc-rs.
+//!
+//! The engine has no dependency on a terminal, so it can be driven by bots and
+//! other tools as well as by the terminal front-end. The default features only build
+//! the terminal front-end, so depend on the crate with `default-features = false`
+
+// Struct fields are always initialized by name throughout the project
+#![allow(clippy::redundant_field_names)]
+
+pub mod ars;
+pub mod board;
+pub mod engine;
+pub mod mode;
+pub mod nrs;
+pub mod randomizer;
+pub mod rotation;
+pub mod scoring;
+pub mod srs;
+pub mod tetromino;
@@ -1,23 +1,311 @@
+// Struct fields are always initialized by name throughout the project
+#![allow(clippy::redundant_field_names)]
+
+#[cfg(feature = "rustbox")]
 extern crate rustbox;
+extern crate yatc_rs;
 
+#[cfg(feature = "rustbox")]
 use self::rustbox::RustBox;
 use std::default::Default;
-use std::error::Error;
+use std::env;
+use std::path::PathBuf;
+use std::process;
+
+use yatc_rs::board::{HEIGHT, MIN_SIZE, PREVIEWS, SKYLINE, WIDTH};
+use yatc_rs::mode;
+use yatc_rs::randomizer;
+use yatc_rs::rotation;
+use yatc_rs::srs::HalfKicks;
 
-mod board;
+use bindings::Bindings;
+use handling::Handling;
+use replay::Replay;
+use scores::HighScores;
+
+mod bindings;
 mod game;
-mod tetromino;
-mod srs;
+mod handling;
+mod input;
+mod menu;
+mod replay;
+mod scores;
+mod surface;
 mod ui;
 mod window;
 
+// The game has to be drawn with at least one of the terminal backends
+#[cfg(not(any(feature = "rustbox", feature = "crossterm")))]
+compile_error!("yatc-rs needs the rustbox or crossterm feature to draw to the terminal");
+
+// The backend used unless another is picked, which is rustbox whenever it was built
+#[cfg(feature = "rustbox")]
+const BACKEND: &str = "rustbox";
+#[cfg(not(feature = "rustbox"))]
+const BACKEND: &str = "crossterm";
+
+const USAGE: &str = "Usage: yatc-rs [--config <path>] [--replay <path>] [--backend <rustbox|crossterm>] [--mode <marathon|marathon-200|endless|sprint|ultra|zen>] [--randomizer <7-bag|14-bag|random|nes|tgm|tgm2>] [--seed <number>] [--previews <0-6>] [--width <columns>] [--height <rows>] [--buffer <rows>] [--rotation <srs|srs+|ars|nrs>] [--half-kicks <tetrio|none>] [--no-irs] [--no-ihs] [--are <time>] [--line-clear-delay <time>] [--partial-lock-out] [--das <time>] [--arr <time>] [--sdf <factor|inf>]";
+
+/// Settings chosen by the player on the command line
+struct Options {
+    config: Option<PathBuf>,
+    replay: Option<PathBuf>,
+    backend: String,
+    mode: String,
+    randomizer: String,
+    seed: Option<u64>,
+    previews: usize,
+    width: usize,
+    height: usize,
+    buffer: usize,
+    rotation: String,
+    half_kicks: HalfKicks,
+    irs: bool,
+    ihs: bool,
+    entry_delay: usize,
+    clear_delay: usize,
+    partial_lock_out: bool,
+    handling: Handling,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            config: None,
+            replay: None,
+            backend: String::from(BACKEND),
+            mode: String::from("marathon"),
+            randomizer: String::from("7-bag"),
+            seed: None,
+            previews: PREVIEWS,
+            width: WIDTH,
+            height: HEIGHT - SKYLINE,
+            buffer: SKYLINE,
+            rotation: String::from("srs"),
+            half_kicks: HalfKicks::Tetrio,
+            irs: true,
+            ihs: true,
+            entry_delay: 0,
+            clear_delay: 0,
+            partial_lock_out: false,
+            handling: Handling::default(),
+        }
+    }
+}
+
+/// Parses the command line arguments into Options
+fn parse_args() -> Result<Options, String> {
+    let mut options = Options::default();
+    let mut args = env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--config" => {
+                options.config = Some(PathBuf::from(args.next().ok_or("--config requires a value")?));
+            },
+
+            "--replay" => {
+                options.replay = Some(PathBuf::from(args.next().ok_or("--replay requires a value")?));
+            },
+
+            "--backend" => {
+                options.backend = args.next().ok_or("--backend requires a value")?;
+            },
+
+            "--mode" => {
+                let name = args.next().ok_or("--mode requires a value")?;
+
+                if mode::from_name(&name).is_none() {
+                    return Err(format!("Unknown mode '{}'", name));
+                }
+
+                options.mode = name;
+            },
+
+            "--randomizer" => {
+                let name = args.next().ok_or("--randomizer requires a value")?;
+
+                if randomizer::from_name(&name, 0).is_none() {
+                    return Err(format!("Unknown randomizer '{}'", name));
+                }
+
+                options.randomizer = name;
+            },
+
+            "--seed" => {
+                let seed = args.next().ok_or("--seed requires a value")?;
+                options.seed = Some(seed.parse().map_err(|_| format!("Invalid seed '{}'", seed))?);
+            },
+
+            "--previews" => {
+                let previews = args.next().ok_or("--previews requires a value")?;
+
+                options.previews = match previews.parse() {
+                    Ok(previews) if previews <= PREVIEWS => previews,
+                    _ => return Err(format!("Invalid number of previews '{}'", previews)),
+                };
+            },
+
+            "--width" => {
+                let width = args.next().ok_or("--width requires a value")?;
+
+                // Every Tetromino has to fit across the field
+                options.width = match width.parse() {
+                    Ok(width) if width >= MIN_SIZE => width,
+                    _ => return Err(format!("Invalid width '{}'", width)),
+                };
+            },
+
+            "--height" => {
+                let height = args.next().ok_or("--height requires a value")?;
+
+                options.height = match height.parse() {
+                    Ok(height) if height >= MIN_SIZE => height,
+                    _ => return Err(format!("Invalid height '{}'", height)),
+                };
+            },
+
+            "--buffer" => {
+                let buffer = args.next().ok_or("--buffer requires a value")?;
+
+                // Tetrominos spawn in the two rows above the visible field
+                options.buffer = match buffer.parse() {
+                    Ok(buffer) if buffer >= SKYLINE => buffer,
+                    _ => return Err(format!("Invalid buffer height '{}'", buffer)),
+                };
+            },
+
+            "--rotation" => {
+                let name = args.next().ok_or("--rotation requires a value")?;
+
+                if rotation::from_name(&name, HalfKicks::Tetrio).is_none() {
+                    return Err(format!("Unknown rotation system '{}'", name));
+                }
+
+                options.rotation = name;
+            },
+
+            "--half-kicks" => {
+                let half_kicks = args.next().ok_or("--half-kicks requires a value")?;
+
+                options.half_kicks = match half_kicks.as_str() {
+                    "tetrio" => HalfKicks::Tetrio,
+                    "none" => HalfKicks::None,
+                    _ => return Err(format!("Unknown 180 kicks '{}'", half_kicks)),
+                };
+            },
+
+            "--no-irs" => options.irs = false,
+            "--no-ihs" => options.ihs = false,
+
+            "--are" => {
+                let are = args.next().ok_or("--are requires a value")?;
+                options.entry_delay = handling::parse_frames(&are)?;
+            },
+
+            "--line-clear-delay" => {
+                let delay = args.next().ok_or("--line-clear-delay requires a value")?;
+                options.clear_delay = handling::parse_frames(&delay)?;
+            },
+
+            "--partial-lock-out" => options.partial_lock_out = true,
+
+            "--das" => {
+                let das = args.next().ok_or("--das requires a value")?;
+                options.handling.das = handling::parse_frames(&das)?;
+            },
+
+            "--arr" => {
+                let arr = args.next().ok_or("--arr requires a value")?;
+                options.handling.arr = handling::parse_frames(&arr)?;
+            },
+
+            "--sdf" => {
+                let sdf = args.next().ok_or("--sdf requires a value")?;
+                options.handling.sdf = handling::parse_sdf(&sdf)?;
+            },
+
+            _ => return Err(format!("Unknown argument '{}'", arg)),
+        }
+    }
+
+    Ok(options)
+}
+
+/// Runs a Game, or plays back a replay in it if there is one
+fn play(mut game: game::Game, replay: Option<Replay>) {
+    match replay {
+        Some(ref replay) => game.watch(replay),
+        None => game.run(),
+    }
+}
+
 fn main() {
-    // Create a RustBox instance to handle output to the terminal
-    let rb = match RustBox::init(Default::default()) {
-        Result::Ok(v) => v,
-        Result::Err(e) => panic!("{}", e.description()),
-    };
-
-    // Start the game
-    game::Game::new(&rb).run();
+    let mut options = parse_args().unwrap_or_else(|e| {
+        eprintln!("{}\n{}", e, USAGE);
+        process::exit(1);
+    });
+
+    let bindings = Bindings::load(options.config.as_deref()).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(1);
+    });
+
+    // A broken high score file is left alone rather than saved over, so each game that
+    // makes a table shows SCORE NOT SAVED until the file is fixed or removed
+    let scores = HighScores::load().unwrap_or_else(HighScores::unreadable);
+
+    // A replay is played back with the settings it was recorded with
+    let replay = options.replay.as_ref().map(|path| {
+        Replay::load(path).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            process::exit(1);
+        })
+    });
+
+    if let Some(ref replay) = replay {
+        replay.configure(&mut options);
+    }
+
+    match options.backend.as_str() {
+        #[cfg(feature = "rustbox")]
+        "rustbox" => {
+
+            // Create a RustBox instance to handle output to the terminal
+            let rb = match RustBox::init(Default::default()) {
+                Result::Ok(v) => v,
+                Result::Err(e) => panic!("{}", e),
+            };
+
+            // Start the game
+            play(game::Game::new(&rb, &rb, bindings, options, scores), replay);
+        },
+
+        #[cfg(not(feature = "rustbox"))]
+        "rustbox" => {
+            eprintln!("yatc-rs was built without the rustbox feature");
+            process::exit(1);
+        },
+
+        #[cfg(feature = "crossterm")]
+        "crossterm" => {
+            let ct = surface::Crossterm::init().unwrap_or_else(|e| {
+                eprintln!("Unable to initialize the terminal: {}", e);
+                process::exit(1);
+            });
+
+            play(game::Game::new(&ct, &ct, bindings, options, scores), replay);
+        },
+
+        #[cfg(not(feature = "crossterm"))]
+        "crossterm" => {
+            eprintln!("yatc-rs was built without the crossterm feature");
+            process::exit(1);
+        },
+
+        _ => {
+            eprintln!("Unknown backend '{}'\n{}", options.backend, USAGE);
+            process::exit(1);
+        },
+    }
 }
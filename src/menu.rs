@@ -0,0 +1,94 @@
+use super::input::Key;
+use super::surface::{Color, Style};
+use super::window::Window;
+
+/// Something the player did with a Menu
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Choice {
+
+    /// The item at an index was picked with Enter
+    Pick(usize),
+
+    /// The value of the item at an index was stepped left or right
+    Adjust(usize, isize),
+
+    /// The Menu was left with Esc
+    Back,
+}
+
+/// A titled list of items that the player moves through with the arrow keys
+pub struct Menu {
+    title: &'static str,
+    items: Vec<String>,
+    selected: usize,
+}
+
+impl Menu {
+
+    /// Initializes a new Menu struct with the first item selected
+    pub fn new(title: &'static str, items: Vec<String>) -> Self {
+        Menu {
+            title: title,
+            items: items,
+            selected: 0,
+        }
+    }
+
+    /// Handles a key pressed while the Menu is shown. Moving up and down wraps
+    /// around the ends of the list
+    pub fn handle(&mut self, key: Key) -> Option<Choice> {
+        match key {
+            Key::Up => {
+                self.selected = (self.selected + self.items.len() - 1) % self.items.len();
+                None
+            },
+
+            Key::Down => {
+                self.selected = (self.selected + 1) % self.items.len();
+                None
+            },
+
+            Key::Left => Some(Choice::Adjust(self.selected, -1)),
+            Key::Right => Some(Choice::Adjust(self.selected, 1)),
+            Key::Enter => Some(Choice::Pick(self.selected)),
+            Key::Esc => Some(Choice::Back),
+            _ => None,
+        }
+    }
+
+    /// Prints the Menu inside the borders of a Window, with its title on the first row
+    pub fn print(&self, window: &Window) {
+        window.clear();
+        window.print_borders(Style::Normal, Color::White, Color::Black);
+        window.print(2, 1, Style::Bold, Color::White, Color::Black, self.title);
+        self.print_items(window, 3);
+    }
+
+    /// Prints the items of the Menu onto a Window starting at a row, marking the selected item
+    pub fn print_items(&self, window: &Window, row: usize) {
+        for (i, item) in self.items.iter().enumerate() {
+            if i == self.selected {
+                window.print(2, row + i, Style::Bold, Color::White, Color::Black, &format!("> {}", item));
+            } else {
+                window.print(2, row + i, Style::Normal, Color::White, Color::Black, &format!("  {}", item));
+            }
+        }
+    }
+
+    /// Gets the number of rows needed to print the Menu inside the borders of a Window
+    pub fn height(&self) -> usize {
+        self.items.len() + 4
+    }
+
+    // GETTERS / SETTERS
+
+    pub fn set_selected(&mut self, selected: usize) {
+        self.selected = selected.min(self.items.len().saturating_sub(1));
+    }
+
+    /// Replaces the items of the Menu, such as when the values they show have changed
+    pub fn set_items(&mut self, items: Vec<String>) {
+        self.items = items;
+        self.selected = self.selected.min(self.items.len().saturating_sub(1));
+    }
+}
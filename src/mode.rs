@@ -0,0 +1,286 @@
+use super::board::{Board, FPS};
+
+// The number of lines cleared to advance a level in Marathon
+const LINES_PER_LEVEL: usize = 10;
+
+// The number of lines to clear in Sprint
+const SPRINT_LINES: usize = 40;
+
+// The length of a game of Ultra in seconds
+const ULTRA_SECONDS: usize = 120;
+
+/// A labelled value shown to the player, such as the score or the time
+pub type Stat = (&'static str, String);
+
+/// A set of rules for how a game progresses and when it ends
+pub trait GameMode: Send {
+
+    /// Gets the name of the mode as shown to the player
+    fn name(&self) -> &'static str;
+
+    /// Gets the level reached after clearing a number of lines
+    fn level(&self, _cleared: usize) -> usize {
+        0
+    }
+
+    /// Determines if Tetrominos fall on their own
+    fn has_gravity(&self) -> bool {
+        true
+    }
+
+    /// Determines if topping out ends the game. If not, a Tetromino that spawns on top
+    /// of the stack clears the field instead, and locking above the skyline is allowed
+    fn can_top_out(&self) -> bool {
+        true
+    }
+
+    /// Determines if the player has reached the goal of the mode, ending the game
+    fn is_complete(&self, _board: &Board) -> bool {
+        false
+    }
+
+    /// Determines if games are ranked by how quickly the goal was reached instead of by
+    /// their score. Only games that reach the goal are ranked this way
+    fn ranks_by_time(&self) -> bool {
+        false
+    }
+
+    /// Gets the stats shown beside the board while playing
+    fn hud(&self, board: &Board) -> Vec<Stat>;
+
+    /// Gets the stats shown once the game is over
+    fn results(&self, board: &Board) -> Vec<Stat>;
+}
+
+/// The names of every GameMode that can be created by from_name
+pub const NAMES: [&str; 6] = ["marathon", "marathon-200", "endless", "sprint", "ultra", "zen"];
+
+/// Creates the GameMode associated with a name
+pub fn from_name(name: &str) -> Option<Box<dyn GameMode>> {
+    match name {
+        "marathon"     => Some(Box::new(Marathon::new(150))),
+        "marathon-200" => Some(Box::new(Marathon::new(200))),
+        "endless"      => Some(Box::new(Marathon::endless())),
+        "sprint"       => Some(Box::new(Sprint::new())),
+        "ultra"        => Some(Box::new(Ultra::new())),
+        "zen"          => Some(Box::new(Zen::new())),
+        _ => None,
+    }
+}
+
+/// Formats a number of frames as minutes, seconds and hundredths
+pub fn format_time(frames: usize) -> String {
+    let centis = (frames * 100) / FPS as usize;
+    format!("{}:{:02}.{:02}", centis / 6000, (centis / 100) % 60, centis % 100)
+}
+
+/// Formats the average number of Tetrominos locked every second
+fn format_pps(board: &Board) -> String {
+    let pps = if board.frames() > 0 {
+        (board.pieces() as f64 * FPS as f64) / board.frames() as f64
+    } else {
+        0.0
+    };
+
+    format!("{:.2}", pps)
+}
+
+/// Clears lines to level up as gravity speeds up, until a number of lines
+/// have been cleared or forever
+pub struct Marathon {
+    lines: Option<usize>,
+}
+
+impl Marathon {
+
+    /// Initializes a new Marathon struct that is complete after clearing a number of lines
+    pub fn new(lines: usize) -> Self {
+        Marathon {
+            lines: Some(lines),
+        }
+    }
+
+    /// Initializes a new Marathon struct that only ends by topping out
+    pub fn endless() -> Self {
+        Marathon {
+            lines: None,
+        }
+    }
+}
+
+impl GameMode for Marathon {
+    fn name(&self) -> &'static str {
+        "MARATHON"
+    }
+
+    fn level(&self, cleared: usize) -> usize {
+        cleared / LINES_PER_LEVEL
+    }
+
+    fn is_complete(&self, board: &Board) -> bool {
+        self.lines.is_some_and(|lines| board.cleared() >= lines)
+    }
+
+    fn hud(&self, board: &Board) -> Vec<Stat> {
+        vec![
+            ("SCORE", board.score().to_string()),
+            ("LEVEL", board.level().to_string()),
+            ("LINES", board.cleared().to_string()),
+        ]
+    }
+
+    fn results(&self, board: &Board) -> Vec<Stat> {
+        vec![
+            ("SCORE", board.score().to_string()),
+            ("LINES", board.cleared().to_string()),
+            ("LEVEL", board.level().to_string()),
+            ("PERFECT", board.perfect_clears().to_string()),
+        ]
+    }
+}
+
+/// Clears 40 lines as quickly as possible
+pub struct Sprint;
+
+impl Sprint {
+
+    /// Initializes a new Sprint struct
+    pub fn new() -> Self {
+        Sprint
+    }
+}
+
+impl Default for Sprint {
+    fn default() -> Self {
+        Sprint::new()
+    }
+}
+
+impl GameMode for Sprint {
+    fn name(&self) -> &'static str {
+        "SPRINT"
+    }
+
+    fn is_complete(&self, board: &Board) -> bool {
+        board.cleared() >= SPRINT_LINES
+    }
+
+    fn ranks_by_time(&self) -> bool {
+        true
+    }
+
+    fn hud(&self, board: &Board) -> Vec<Stat> {
+        vec![
+            ("TIME", format_time(board.frames())),
+            ("LINES", format!("{}/{}", board.cleared().min(SPRINT_LINES), SPRINT_LINES)),
+            ("PIECES", board.pieces().to_string()),
+        ]
+    }
+
+    fn results(&self, board: &Board) -> Vec<Stat> {
+        vec![
+            ("TIME", format_time(board.frames())),
+            ("LINES", board.cleared().to_string()),
+            ("PIECES", board.pieces().to_string()),
+            ("PPS", format_pps(board)),
+            ("PERFECT", board.perfect_clears().to_string()),
+        ]
+    }
+}
+
+/// Scores as many points as possible in two minutes
+pub struct Ultra;
+
+impl Ultra {
+
+    /// Initializes a new Ultra struct
+    pub fn new() -> Self {
+        Ultra
+    }
+
+    /// Gets the number of frames left before time is up
+    fn remaining(board: &Board) -> usize {
+        (ULTRA_SECONDS * FPS as usize).saturating_sub(board.frames())
+    }
+}
+
+impl Default for Ultra {
+    fn default() -> Self {
+        Ultra::new()
+    }
+}
+
+impl GameMode for Ultra {
+    fn name(&self) -> &'static str {
+        "ULTRA"
+    }
+
+    fn is_complete(&self, board: &Board) -> bool {
+        Ultra::remaining(board) == 0
+    }
+
+    fn hud(&self, board: &Board) -> Vec<Stat> {
+        vec![
+            ("TIME", format_time(Ultra::remaining(board))),
+            ("SCORE", board.score().to_string()),
+            ("LINES", board.cleared().to_string()),
+        ]
+    }
+
+    fn results(&self, board: &Board) -> Vec<Stat> {
+        vec![
+            ("SCORE", board.score().to_string()),
+            ("LINES", board.cleared().to_string()),
+            ("PERFECT", board.perfect_clears().to_string()),
+            ("PPS", format_pps(board)),
+        ]
+    }
+}
+
+/// Plays without gravity or topping out until the player stops
+pub struct Zen;
+
+impl Zen {
+
+    /// Initializes a new Zen struct
+    pub fn new() -> Self {
+        Zen
+    }
+}
+
+impl Default for Zen {
+    fn default() -> Self {
+        Zen::new()
+    }
+}
+
+impl GameMode for Zen {
+    fn name(&self) -> &'static str {
+        "ZEN"
+    }
+
+    fn has_gravity(&self) -> bool {
+        false
+    }
+
+    fn can_top_out(&self) -> bool {
+        false
+    }
+
+    fn hud(&self, board: &Board) -> Vec<Stat> {
+        vec![
+            ("SCORE", board.score().to_string()),
+            ("LINES", board.cleared().to_string()),
+            ("PIECES", board.pieces().to_string()),
+        ]
+    }
+
+    fn results(&self, board: &Board) -> Vec<Stat> {
+        vec![
+            ("SCORE", board.score().to_string()),
+            ("LINES", board.cleared().to_string()),
+            ("PIECES", board.pieces().to_string()),
+            ("PERFECT", board.perfect_clears().to_string()),
+        ]
+    }
+}
@@ -0,0 +1,208 @@
+use super::board::Field;
+use super::rotation;
+use super::rotation::{Direction, RotationSystem};
+use super::tetromino::{
+    Point,
+    Rotation,
+    Tetromino,
+    TetrominoType,
+    MINOS,
+    ROTS,
+    TYPES,
+};
+
+/// The Nintendo Rotation System used by Tetris on the NES. Tetrominos spawn
+/// pointing down and rotate about a fixed center without any wall kicks
+pub struct Nrs;
+
+impl Nrs {
+
+    /// Initializes a new Nrs struct
+    pub fn new() -> Self {
+        Nrs
+    }
+}
+
+impl Default for Nrs {
+    fn default() -> Self {
+        Nrs::new()
+    }
+}
+
+impl RotationSystem for Nrs {
+    fn tetromino(&self, origin: Point, tetromino_type: TetrominoType, rot: Rotation) -> Tetromino {
+        Tetromino::from_minos(origin, tetromino_type, rot, SHAPES[tetromino_type as usize][rot as usize])
+    }
+
+    fn spawn_point(&self, tetromino_type: TetrominoType, width: usize) -> Point {
+        let x = rotation::center(width, BOX_WIDTH);
+
+        // Every Tetromino spawns around the column right of the middle, so the
+        // narrower bounding boxes start a column further to the right
+        match tetromino_type {
+            TetrominoType::I | TetrominoType::O => Point { x: x, y: SPAWN_ROW },
+            _ => Point { x: x + 1, y: SPAWN_ROW },
+        }
+    }
+
+    fn rotate(&self, field: &Field, tetromino: &Tetromino, dir: Direction) -> Option<(Tetromino, usize)> {
+
+        // Tetrominos can't be rotated by 180 degrees
+        if dir == Direction::Half {
+            return None;
+        }
+
+        let rot = rotation::next_rotation(tetromino.rot(), dir);
+        let rotated = self.tetromino(tetromino.origin(), tetromino.tetromino_type(), rot);
+
+        if rotation::fits(field, &rotated, Point { x: 0, y: 0 }) {
+            Some((rotated, 0))
+        } else {
+            None
+        }
+    }
+}
+
+// Tetrominos spawn with the top of their bounding box two rows above the skyline. The
+// I Tetromino's bounding box is 4 wide, and the others are placed relative to it
+const BOX_WIDTH: usize = 4;
+const SPAWN_ROW: isize = -2;
+
+// The minos of each Tetromino in each Rotation, ordered the same as TetrominoType.
+// The I, S and Z Tetrominos only have two distinct Rotations
+const SHAPES: [[[Point; MINOS]; ROTS]; TYPES] = [
+    // I
+    [
+        [Point { x: 0, y:  1 }, Point { x: 1, y: 1 }, Point { x: 2, y: 1 }, Point { x: 3, y: 1 }],
+        [Point { x: 2, y: -1 }, Point { x: 2, y: 0 }, Point { x: 2, y: 1 }, Point { x: 2, y: 2 }],
+        [Point { x: 0, y:  1 }, Point { x: 1, y: 1 }, Point { x: 2, y: 1 }, Point { x: 3, y: 1 }],
+        [Point { x: 2, y: -1 }, Point { x: 2, y: 0 }, Point { x: 2, y: 1 }, Point { x: 2, y: 2 }],
+    ],
+
+    // J
+    [
+        [Point { x: 0, y: 1 }, Point { x: 1, y: 1 }, Point { x: 2, y: 1 }, Point { x: 2, y: 2 }],
+        [Point { x: 1, y: 0 }, Point { x: 1, y: 1 }, Point { x: 0, y: 2 }, Point { x: 1, y: 2 }],
+        [Point { x: 0, y: 0 }, Point { x: 0, y: 1 }, Point { x: 1, y: 1 }, Point { x: 2, y: 1 }],
+        [Point { x: 1, y: 0 }, Point { x: 2, y: 0 }, Point { x: 1, y: 1 }, Point { x: 1, y: 2 }],
+    ],
+
+    // L
+    [
+        [Point { x: 0, y: 1 }, Point { x: 1, y: 1 }, Point { x: 2, y: 1 }, Point { x: 0, y: 2 }],
+        [Point { x: 0, y: 0 }, Point { x: 1, y: 0 }, Point { x: 1, y: 1 }, Point { x: 1, y: 2 }],
+        [Point { x: 2, y: 0 }, Point { x: 0, y: 1 }, Point { x: 1, y: 1 }, Point { x: 2, y: 1 }],
+        [Point { x: 1, y: 0 }, Point { x: 1, y: 1 }, Point { x: 1, y: 2 }, Point { x: 2, y: 2 }],
+    ],
+
+    // O
+    [
+        [Point { x: 1, y: 1 }, Point { x: 2, y: 1 }, Point { x: 1, y: 2 }, Point { x: 2, y: 2 }],
+        [Point { x: 1, y: 1 }, Point { x: 2, y: 1 }, Point { x: 1, y: 2 }, Point { x: 2, y: 2 }],
+        [Point { x: 1, y: 1 }, Point { x: 2, y: 1 }, Point { x: 1, y: 2 }, Point { x: 2, y: 2 }],
+        [Point { x: 1, y: 1 }, Point { x: 2, y: 1 }, Point { x: 1, y: 2 }, Point { x: 2, y: 2 }],
+    ],
+
+    // S
+    [
+        [Point { x: 1, y: 1 }, Point { x: 2, y: 1 }, Point { x: 0, y: 2 }, Point { x: 1, y: 2 }],
+        [Point { x: 1, y: 0 }, Point { x: 1, y: 1 }, Point { x: 2, y: 1 }, Point { x: 2, y: 2 }],
+        [Point { x: 1, y: 1 }, Point { x: 2, y: 1 }, Point { x: 0, y: 2 }, Point { x: 1, y: 2 }],
+        [Point { x: 1, y: 0 }, Point { x: 1, y: 1 }, Point { x: 2, y: 1 }, Point { x: 2, y: 2 }],
+    ],
+
+    // T
+    [
+        [Point { x: 0, y: 1 }, Point { x: 1, y: 1 }, Point { x: 2, y: 1 }, Point { x: 1, y: 2 }],
+        [Point { x: 1, y: 0 }, Point { x: 0, y: 1 }, Point { x: 1, y: 1 }, Point { x: 1, y: 2 }],
+        [Point { x: 1, y: 0 }, Point { x: 0, y: 1 }, Point { x: 1, y: 1 }, Point { x: 2, y: 1 }],
+        [Point { x: 1, y: 0 }, Point { x: 1, y: 1 }, Point { x: 2, y: 1 }, Point { x: 1, y: 2 }],
+    ],
+
+    // Z
+    [
+        [Point { x: 0, y: 1 }, Point { x: 1, y: 1 }, Point { x: 1, y: 2 }, Point { x: 2, y: 2 }],
+        [Point { x: 2, y: 0 }, Point { x: 1, y: 1 }, Point { x: 2, y: 1 }, Point { x: 1, y: 2 }],
+        [Point { x: 0, y: 1 }, Point { x: 1, y: 1 }, Point { x: 1, y: 2 }, Point { x: 2, y: 2 }],
+        [Point { x: 2, y: 0 }, Point { x: 1, y: 1 }, Point { x: 2, y: 1 }, Point { x: 1, y: 2 }],
+    ],
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TYPES: [TetrominoType; 7] = [
+        TetrominoType::I,
+        TetrominoType::J,
+        TetrominoType::L,
+        TetrominoType::O,
+        TetrominoType::S,
+        TetrominoType::T,
+        TetrominoType::Z,
+    ];
+
+    const ROTATIONS: [Rotation; ROTS] = [Rotation::Spawn, Rotation::Right, Rotation::Rot2, Rotation::Left];
+
+    const ORIGIN: Point = Point { x: 3, y: 10 };
+
+    #[test]
+    fn rotation_in_open_field() {
+        let nrs = Nrs::new();
+        let field = vec![vec![None; 10]; 22];
+
+        for &tetromino_type in TYPES.iter() {
+            for &rot in ROTATIONS.iter() {
+                let tetromino = nrs.tetromino(ORIGIN, tetromino_type, rot);
+
+                for &dir in [Direction::Clockwise, Direction::CounterClockwise].iter() {
+                    let (rotated, kick) = nrs.rotate(&field, &tetromino, dir).unwrap();
+
+                    assert_eq!(rotated.rot(), rotation::next_rotation(rot, dir));
+                    assert_eq!(rotated.origin(), ORIGIN);
+                    assert_eq!(kick, 0);
+                }
+
+                assert!(nrs.rotate(&field, &tetromino, Direction::Half).is_none());
+            }
+        }
+    }
+
+    #[test]
+    fn never_kicks() {
+        let nrs = Nrs::new();
+        let field = vec![vec![None; 10]; 22];
+
+        // A T pointing left against the wall has no room to point down
+        let tetromino = nrs.tetromino(Point { x: -1, y: 10 }, TetrominoType::T, Rotation::Left);
+        assert!(nrs.rotate(&field, &tetromino, Direction::Clockwise).is_none());
+
+        // Nor does a vertical I, even with room to lie down a column to the left
+        let mut field = vec![vec![None; 10]; 22];
+        field[ORIGIN.y as usize + 1][ORIGIN.x as usize + 3] = Some(TetrominoType::Z);
+
+        let tetromino = nrs.tetromino(ORIGIN, TetrominoType::I, Rotation::Right);
+        assert!(nrs.rotate(&field, &tetromino, Direction::Clockwise).is_none());
+    }
+
+    #[test]
+    fn spawns_right_of_the_middle() {
+        let nrs = Nrs::new();
+
+        for &tetromino_type in TYPES.iter() {
+            let tetromino = nrs.spawn(tetromino_type, 10, 2);
+            let columns = tetromino.minos().iter().map(|mino| tetromino.origin().x + mino.x).collect::<Vec<_>>();
+            let rows = tetromino.minos().iter().map(|mino| tetromino.origin().y + mino.y).collect::<Vec<_>>();
+
+            // Every Tetromino covers the column right of the middle, straddling the skyline
+            assert!(columns.contains(&5), "{:?}", tetromino_type);
+            assert!(rows.iter().all(|&row| row == 1 || row == 2), "{:?}", tetromino_type);
+            assert_eq!(tetromino.rot(), Rotation::Spawn);
+        }
+
+        // The T points down, with its stem under the middle
+        let t = nrs.spawn(TetrominoType::T, 10, 2);
+        assert!(t.minos().iter().any(|&mino| t.origin() + mino == Point { x: 5, y: 2 }));
+        assert_eq!(t.minos().iter().filter(|&&mino| (t.origin() + mino).y == 1).count(), 3);
+    }
+}
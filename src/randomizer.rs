@@ -0,0 +1,252 @@
+extern crate rand;
+
+use self::rand::{Rng, SeedableRng, XorShiftRng};
+
+use super::tetromino::TetrominoType;
+
+const PIECES: [TetrominoType; 7] = [
+    TetrominoType::I,
+    TetrominoType::J,
+    TetrominoType::L,
+    TetrominoType::O,
+    TetrominoType::S,
+    TetrominoType::T,
+    TetrominoType::Z,
+];
+
+/// A generator for the sequence of Tetrominos handed out to the player
+pub trait Randomizer: Send {
+
+    /// Generates the next TetrominoType in the sequence
+    fn next(&mut self) -> TetrominoType;
+}
+
+/// The names of every Randomizer that can be created by from_name
+pub const NAMES: [&str; 6] = ["7-bag", "14-bag", "random", "nes", "tgm", "tgm2"];
+
+/// Creates the Randomizer associated with a name, seeded so that the same
+/// seed always produces the same sequence of Tetrominos
+pub fn from_name(name: &str, seed: u64) -> Option<Box<dyn Randomizer>> {
+    match name {
+        "7-bag"  => Some(Box::new(Bag::new(1, seed))),
+        "14-bag" => Some(Box::new(Bag::new(2, seed))),
+        "random" => Some(Box::new(Random::new(seed))),
+        "nes"    => Some(Box::new(Nes::new(seed))),
+        "tgm"    => Some(Box::new(Tgm::new(4, seed))),
+        "tgm2"   => Some(Box::new(Tgm::new(6, seed))),
+        _ => None,
+    }
+}
+
+/// Generates a new seed for a game
+pub fn random_seed() -> u64 {
+    rand::random()
+}
+
+/// Creates a random number generator from a seed
+fn seeded(seed: u64) -> XorShiftRng {
+
+    // XorShift cannot be seeded with all zeros, so mix in some constant bits
+    XorShiftRng::from_seed([seed as u32, (seed >> 32) as u32, 0x9E37_79B9, 0x7F4A_7C15])
+}
+
+/// Deals out shuffled bags containing a number of copies of every Tetromino
+pub struct Bag {
+    bag: Vec<TetrominoType>,
+    copies: usize,
+    rng: XorShiftRng,
+}
+
+impl Bag {
+
+    /// Initializes a new Bag struct holding a number of copies of each Tetromino.
+    /// Panics if there are no copies, since the bag could never be filled
+    pub fn new(copies: usize, seed: u64) -> Self {
+        assert!(copies > 0, "a Bag needs at least one copy of each Tetromino");
+
+        Bag {
+            bag: vec![],
+            copies: copies,
+            rng: seeded(seed),
+        }
+    }
+}
+
+impl Randomizer for Bag {
+    fn next(&mut self) -> TetrominoType {
+
+        // All of the pieces have been picked, so refill the bag and reshuffle it
+        if self.bag.is_empty() {
+            for _ in 0..self.copies {
+                self.bag.extend_from_slice(&PIECES);
+            }
+
+            self.rng.shuffle(&mut self.bag);
+        }
+
+        self.bag.pop().unwrap()
+    }
+}
+
+/// Picks every Tetromino independently with equal probability
+pub struct Random {
+    rng: XorShiftRng,
+}
+
+impl Random {
+
+    /// Initializes a new Random struct
+    pub fn new(seed: u64) -> Self {
+        Random {
+            rng: seeded(seed),
+        }
+    }
+}
+
+impl Randomizer for Random {
+    fn next(&mut self) -> TetrominoType {
+        *self.rng.choose(&PIECES).unwrap()
+    }
+}
+
+/// Picks Tetrominos like the NES version, rerolling once to avoid repeats
+pub struct Nes {
+    last: Option<TetrominoType>,
+    rng: XorShiftRng,
+}
+
+impl Nes {
+
+    /// Initializes a new Nes struct
+    pub fn new(seed: u64) -> Self {
+        Nes {
+            last: None,
+            rng: seeded(seed),
+        }
+    }
+}
+
+impl Randomizer for Nes {
+    fn next(&mut self) -> TetrominoType {
+
+        // The eighth roll acts as a reroll, just as a repeat of the last piece does
+        let roll = self.rng.gen_range(0, PIECES.len() + 1);
+
+        let piece = if roll == PIECES.len() || self.last == Some(PIECES[roll]) {
+            PIECES[self.rng.gen_range(0, PIECES.len())]
+        } else {
+            PIECES[roll]
+        };
+
+        self.last = Some(piece);
+        piece
+    }
+}
+
+/// Picks Tetrominos like the TGM series, rerolling a limited number of times to
+/// avoid any of the last four pieces
+pub struct Tgm {
+    history: [TetrominoType; 4],
+    rolls: usize,
+    is_first: bool,
+    rng: XorShiftRng,
+}
+
+impl Tgm {
+
+    /// Initializes a new Tgm struct that rolls up to a number of times per piece
+    pub fn new(rolls: usize, seed: u64) -> Self {
+        Tgm {
+            history: [TetrominoType::Z; 4],
+            rolls: rolls,
+            is_first: true,
+            rng: seeded(seed),
+        }
+    }
+}
+
+impl Randomizer for Tgm {
+    fn next(&mut self) -> TetrominoType {
+
+        // The first piece is never an S, Z or O to avoid forcing an overhang
+        let piece = if self.is_first {
+            self.is_first = false;
+            *self.rng.choose(&[TetrominoType::I, TetrominoType::J, TetrominoType::L, TetrominoType::T]).unwrap()
+        }
+
+        else {
+            let mut piece = *self.rng.choose(&PIECES).unwrap();
+
+            for _ in 1..self.rolls {
+                if !self.history.contains(&piece) {
+                    break;
+                }
+
+                piece = *self.rng.choose(&PIECES).unwrap();
+            }
+
+            piece
+        };
+
+        // Push the piece into the history, forgetting the oldest one
+        for i in 0..self.history.len() - 1 {
+            self.history[i] = self.history[i + 1];
+        }
+
+        self.history[self.history.len() - 1] = piece;
+        piece
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::tetromino::TetrominoType::{I, J, L, O, S, T, Z};
+
+    // Replays and races rely on a seed always dealing the same pieces, so any change to
+    // these sequences breaks every replay saved before it
+    const SEED: u64 = 42;
+
+    /// Gets the first 14 Tetrominos dealt by the Randomizer with a name
+    fn first(name: &str) -> Vec<TetrominoType> {
+        let mut randomizer = from_name(name, SEED).unwrap();
+        (0..14).map(|_| randomizer.next()).collect()
+    }
+
+    #[test]
+    fn seven_bag_order() {
+        assert_eq!(first("7-bag"), [T, O, I, L, S, J, Z, J, Z, I, L, S, T, O]);
+    }
+
+    #[test]
+    fn fourteen_bag_order() {
+        assert_eq!(first("14-bag"), [T, J, J, T, O, I, O, S, I, Z, L, Z, L, S]);
+    }
+
+    #[test]
+    fn random_order() {
+        assert_eq!(first("random"), [T, Z, I, Z, T, L, J, I, I, S, L, L, L, T]);
+    }
+
+    #[test]
+    fn nes_order() {
+        assert_eq!(first("nes"), [Z, S, Z, T, J, O, I, Z, T, O, J, J, L, I]);
+    }
+
+    #[test]
+    fn tgm_order() {
+        assert_eq!(first("tgm"), [T, I, L, J, S, T, O, L, Z, I, O, S, L, J]);
+    }
+
+    #[test]
+    fn tgm2_order() {
+        assert_eq!(first("tgm2"), [T, I, L, J, S, T, O, L, Z, I, S, J, T, L]);
+    }
+
+    #[test]
+    fn every_name_is_seeded() {
+        for &name in NAMES.iter() {
+            assert_eq!(first(name), first(name), "{}", name);
+        }
+    }
+}
@@ -0,0 +1,423 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use yatc_rs::board::SKYLINE;
+use yatc_rs::mode;
+use yatc_rs::randomizer;
+use yatc_rs::rotation;
+use yatc_rs::srs::HalfKicks;
+
+use super::{MIN_SIZE, Options};
+use super::bindings::Action;
+use super::handling::{self, Handling};
+use super::scores;
+
+// The first line of every replay file, followed by the version of the format. The
+// version changes whenever older replays would no longer play back the same way
+const MAGIC: &str = "yatc-rs-replay";
+const FORMAT: usize = 1;
+
+// The line separating the header of a replay file from its events
+const EVENTS: &str = "events";
+
+// The code of each Action within a replay file. Actions that don't control the
+// Board, like pausing, are never recorded
+const CODES: [(Action, char); 8] = [
+    (Action::MoveLeft, 'L'),
+    (Action::MoveRight, 'R'),
+    (Action::SoftDrop, 'D'),
+    (Action::HardDrop, 'H'),
+    (Action::RotateClockwise, 'C'),
+    (Action::RotateCounterClockwise, 'W'),
+    (Action::RotateHalf, 'F'),
+    (Action::Hold, 'S'),
+];
+
+/// An Action pressed or released before a frame of a recorded game
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct ActionEvent {
+    pub frame: usize,
+    pub action: Action,
+    pub is_press: bool,
+}
+
+/// A recording of a game, holding everything needed to play it back exactly: the
+/// settings that the Board and Controller were created with, then every input in the
+/// order it was applied.
+///
+/// A replay file is a header of "key value" lines, followed by a line reading "events"
+/// and then one line per ActionEvent. Each ActionEvent is the number of frames since the
+/// last one, then '+' for a press or '-' for a release, then the code of its Action
+pub struct Replay {
+    seed: u64,
+    mode: String,
+    randomizer: String,
+    rotation: String,
+    half_kicks: HalfKicks,
+    width: usize,
+    height: usize,
+    buffer: usize,
+    irs: bool,
+    ihs: bool,
+    entry_delay: usize,
+    clear_delay: usize,
+    partial_lock_out: bool,
+    handling: Handling,
+    has_release: bool,
+    frames: usize,
+    events: Vec<ActionEvent>,
+}
+
+impl Replay {
+
+    /// Initializes a new Replay struct for a game about to be played with the settings
+    /// chosen by the player. has_release states whether the Input reports key releases
+    pub fn new(options: &Options, seed: u64, has_release: bool) -> Self {
+        Replay {
+            seed: seed,
+            mode: options.mode.clone(),
+            randomizer: options.randomizer.clone(),
+            rotation: options.rotation.clone(),
+            half_kicks: options.half_kicks,
+            width: options.width,
+            height: options.height,
+            buffer: options.buffer,
+            irs: options.irs,
+            ihs: options.ihs,
+            entry_delay: options.entry_delay,
+            clear_delay: options.clear_delay,
+            partial_lock_out: options.partial_lock_out,
+            handling: options.handling,
+            has_release: has_release,
+            frames: 0,
+            events: vec![],
+        }
+    }
+
+    /// Loads a Replay from a replay file
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("Unable to read {}: {}", path.display(), e))?;
+
+        Replay::parse(&contents).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// Parses a Replay from the contents of a replay file
+    pub fn parse(contents: &str) -> Result<Self, String> {
+        let mut lines = contents.lines();
+
+        match lines.next().map(|line| line.split_whitespace().collect::<Vec<_>>()) {
+            Some(ref magic) if magic.len() == 2 && magic[0] == MAGIC => {
+                if magic[1] != FORMAT.to_string() {
+                    return Err(format!("Unsupported replay format '{}'", magic[1]));
+                }
+            },
+
+            _ => return Err(String::from("Not a replay file")),
+        }
+
+        // Read the header up to the start of the events. Keys that are no longer used,
+        // like the version of the game that older replays were recorded on, are ignored
+        let mut header = HashMap::new();
+
+        for line in lines.by_ref() {
+            if line == EVENTS {
+                break;
+            }
+
+            let mut parts = line.splitn(2, ' ');
+
+            match (parts.next(), parts.next()) {
+                (Some(key), Some(value)) => header.insert(key, value),
+                _ => return Err(format!("Invalid header line '{}'", line)),
+            };
+        }
+
+        let field = |key: &str| header.get(key).cloned().ok_or_else(|| format!("Missing '{}'", key));
+        let number = |key: &str| -> Result<usize, String> {
+            let value = field(key)?;
+            value.parse().map_err(|_| format!("Invalid {} '{}'", key, value))
+        };
+        let flag = |key: &str| -> Result<bool, String> {
+            let value = field(key)?;
+            value.parse().map_err(|_| format!("Invalid {} '{}'", key, value))
+        };
+
+        let seed = field("seed")?;
+        let half_kicks = field("half-kicks")?;
+
+        let mut replay = Replay {
+            seed: seed.parse().map_err(|_| format!("Invalid seed '{}'", seed))?,
+            mode: String::from(field("mode")?),
+            randomizer: String::from(field("randomizer")?),
+            rotation: String::from(field("rotation")?),
+
+            half_kicks: match half_kicks {
+                "tetrio" => HalfKicks::Tetrio,
+                "none" => HalfKicks::None,
+                _ => return Err(format!("Unknown 180 kicks '{}'", half_kicks)),
+            },
+
+            width: number("width")?,
+            height: number("height")?,
+            buffer: number("buffer")?,
+            irs: flag("irs")?,
+            ihs: flag("ihs")?,
+            entry_delay: number("are")?,
+            clear_delay: number("line-clear-delay")?,
+            partial_lock_out: flag("partial-lock-out")?,
+
+            handling: Handling {
+                das: number("das")?,
+                arr: number("arr")?,
+                sdf: handling::parse_sdf(field("sdf")?)?,
+            },
+
+            has_release: flag("release")?,
+            frames: number("frames")?,
+            events: vec![],
+        };
+
+        // The names are trusted once the game is being played back, so check them now
+        if mode::from_name(&replay.mode).is_none() {
+            return Err(format!("Unknown mode '{}'", replay.mode));
+        }
+
+        if randomizer::from_name(&replay.randomizer, 0).is_none() {
+            return Err(format!("Unknown randomizer '{}'", replay.randomizer));
+        }
+
+        if rotation::from_name(&replay.rotation, replay.half_kicks).is_none() {
+            return Err(format!("Unknown rotation system '{}'", replay.rotation));
+        }
+
+        if replay.width < MIN_SIZE || replay.height < MIN_SIZE || replay.buffer < SKYLINE {
+            return Err(String::from("Invalid board size"));
+        }
+
+        // Each ActionEvent is timed from the one before it
+        let mut frame = 0;
+
+        for line in lines {
+            let sign = line.find(&['+', '-'][..])
+                .ok_or_else(|| format!("Invalid event '{}'", line))?;
+
+            let (delay, rest) = line.split_at(sign);
+            let mut rest = rest.chars();
+
+            let is_press = rest.next() == Some('+');
+            let action = match (rest.next(), rest.next()) {
+                (Some(code), None) => CODES.iter().find(|&&(_, c)| c == code).map(|&(action, _)| action),
+                _ => None,
+            };
+
+            frame += delay.parse::<usize>().map_err(|_| format!("Invalid event '{}'", line))?;
+
+            replay.events.push(ActionEvent {
+                frame: frame,
+                action: action.ok_or_else(|| format!("Invalid event '{}'", line))?,
+                is_press: is_press,
+            });
+        }
+
+        Ok(replay)
+    }
+
+    /// Changes the settings chosen by the player to the ones the game was recorded with
+    pub fn configure(&self, options: &mut Options) {
+        options.seed = Some(self.seed);
+        options.mode = self.mode.clone();
+        options.randomizer = self.randomizer.clone();
+        options.rotation = self.rotation.clone();
+        options.half_kicks = self.half_kicks;
+        options.width = self.width;
+        options.height = self.height;
+        options.buffer = self.buffer;
+        options.irs = self.irs;
+        options.ihs = self.ihs;
+        options.entry_delay = self.entry_delay;
+        options.clear_delay = self.clear_delay;
+        options.partial_lock_out = self.partial_lock_out;
+        options.handling = self.handling;
+    }
+
+    /// Records an Action being pressed or released before a frame. Actions that don't
+    /// control the Board are ignored
+    pub fn record(&mut self, frame: usize, action: Action, is_press: bool) {
+        if CODES.iter().any(|&(a, _)| a == action) {
+            self.events.push(ActionEvent {
+                frame: frame,
+                action: action,
+                is_press: is_press,
+            });
+        }
+    }
+
+    /// Writes the Replay to a new file in the replay directory, named after its mode
+    /// and the time it was saved. Returns the path of the file
+    pub fn save(&self) -> Result<PathBuf, String> {
+        let dir = scores::data_dir().ok_or("Unable to find the data directory")?.join("replays");
+        fs::create_dir_all(&dir).map_err(|e| format!("Unable to create {}: {}", dir.display(), e))?;
+
+        let time = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.as_secs());
+        let path = dir.join(format!("{}-{}.replay", self.mode, time));
+
+        fs::write(&path, self.contents()).map_err(|e| format!("Unable to write {}: {}", path.display(), e))?;
+        Ok(path)
+    }
+
+    /// Converts the Replay into the contents of a replay file
+    pub fn contents(&self) -> String {
+        let half_kicks = match self.half_kicks {
+            HalfKicks::Tetrio => "tetrio",
+            HalfKicks::None => "none",
+        };
+
+        let mut contents = format!("{} {}\n", MAGIC, FORMAT);
+        contents += &format!("seed {}\n", self.seed);
+        contents += &format!("mode {}\n", self.mode);
+        contents += &format!("randomizer {}\n", self.randomizer);
+        contents += &format!("rotation {}\n", self.rotation);
+        contents += &format!("half-kicks {}\n", half_kicks);
+        contents += &format!("width {}\n", self.width);
+        contents += &format!("height {}\n", self.height);
+        contents += &format!("buffer {}\n", self.buffer);
+        contents += &format!("irs {}\n", self.irs);
+        contents += &format!("ihs {}\n", self.ihs);
+        contents += &format!("are {}\n", self.entry_delay);
+        contents += &format!("line-clear-delay {}\n", self.clear_delay);
+        contents += &format!("partial-lock-out {}\n", self.partial_lock_out);
+        contents += &format!("das {}\n", self.handling.das);
+        contents += &format!("arr {}\n", self.handling.arr);
+        contents += &format!("sdf {}\n", self.handling.sdf);
+        contents += &format!("release {}\n", self.has_release);
+        contents += &format!("frames {}\n", self.frames);
+        contents += EVENTS;
+        contents += "\n";
+
+        let mut frame = 0;
+
+        for event in self.events.iter() {
+            let code = CODES.iter().find(|&&(a, _)| a == event.action).map_or('?', |&(_, c)| c);
+            let sign = if event.is_press { '+' } else { '-' };
+
+            contents += &format!("{}{}{}\n", event.frame - frame, sign, code);
+            frame = event.frame;
+        }
+
+        contents
+    }
+
+    // GETTERS / SETTERS
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn has_release(&self) -> bool {
+        self.has_release
+    }
+
+    /// Gets the number of frames that the game lasted
+    pub fn frames(&self) -> usize {
+        self.frames
+    }
+
+    pub fn set_frames(&mut self, frames: usize) {
+        self.frames = frames;
+    }
+
+    pub fn events(&self) -> &[ActionEvent] {
+        &self.events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REPLAY: &str = "yatc-rs-replay 1
+seed 18446744073709551615
+mode sprint
+randomizer 14-bag
+rotation ars
+half-kicks none
+width 4
+height 8
+buffer 3
+irs false
+ihs true
+are 6
+line-clear-delay 20
+partial-lock-out true
+das 8
+arr 0
+sdf inf
+release true
+frames 1234
+events
+0+L
+5-L
+0+C
+12+H
+0-H
+1200-C
+";
+
+    /// Gets the error from parsing a replay file with a line replaced
+    fn error(line: &str, replacement: &str) -> String {
+        let contents = REPLAY.replace(line, replacement);
+        assert_ne!(contents, REPLAY);
+
+        match Replay::parse(&contents) {
+            Ok(_) => panic!("'{}' was parsed", replacement),
+            Err(e) => e,
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let replay = Replay::parse(REPLAY).unwrap();
+
+        assert_eq!(replay.seed(), u64::MAX);
+        assert_eq!(replay.frames(), 1234);
+        assert!(replay.has_release());
+        assert!(replay.handling.sdf.is_infinite());
+        assert_eq!(replay.events().len(), 6);
+        assert_eq!(replay.events()[3], ActionEvent { frame: 17, action: Action::HardDrop, is_press: true });
+        assert_eq!(replay.events()[5], ActionEvent { frame: 1217, action: Action::RotateClockwise, is_press: false });
+
+        assert_eq!(replay.contents(), REPLAY);
+    }
+
+    #[test]
+    fn older_keys_are_ignored() {
+        let contents = REPLAY.replace("seed", "version 0.1.0\nseed");
+        assert_eq!(Replay::parse(&contents).unwrap().contents(), REPLAY);
+    }
+
+    #[test]
+    fn malformed_header() {
+        assert_eq!(error("yatc-rs-replay 1", "yatc-rs 1"), "Not a replay file");
+        assert_eq!(error("yatc-rs-replay 1", "yatc-rs-replay 2"), "Unsupported replay format '2'");
+        assert_eq!(error("mode sprint", "mode"), "Invalid header line 'mode'");
+        assert_eq!(error("mode sprint\n", ""), "Missing 'mode'");
+        assert_eq!(error("mode sprint", "mode tetris"), "Unknown mode 'tetris'");
+        assert_eq!(error("seed 18446744073709551615", "seed 18446744073709551616"), "Invalid seed '18446744073709551616'");
+        assert_eq!(error("width 4", "width four"), "Invalid width 'four'");
+        assert_eq!(error("width 4", "width 3"), "Invalid board size");
+        assert_eq!(error("irs false", "irs no"), "Invalid irs 'no'");
+        assert_eq!(error("half-kicks none", "half-kicks srs"), "Unknown 180 kicks 'srs'");
+    }
+
+    #[test]
+    fn malformed_events() {
+        assert_eq!(error("5-L", "5L"), "Invalid event '5L'");
+        assert_eq!(error("5-L", "5-X"), "Invalid event '5-X'");
+        assert_eq!(error("5-L", "5-LL"), "Invalid event '5-LL'");
+        assert_eq!(error("5-L", "-5-L"), "Invalid event '-5-L'");
+        assert_eq!(error("5-L", "five-L"), "Invalid event 'five-L'");
+    }
+}
@@ -0,0 +1,113 @@
+use super::ars::Ars;
+use super::board::Field;
+use super::nrs::Nrs;
+use super::srs::{HalfKicks, Srs};
+use super::tetromino::{
+    Point,
+    Rotation,
+    Tetromino,
+    TetrominoType,
+};
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Direction {
+    Clockwise,
+    CounterClockwise,
+
+    /// A 180 degree rotation straight to the opposite Rotation
+    Half,
+}
+
+/// A set of rules for how Tetrominos are shaped, where they spawn and how they rotate
+pub trait RotationSystem: Send {
+
+    /// Gets a Tetromino of a specified type and rotation at an origin
+    fn tetromino(&self, origin: Point, tetromino_type: TetrominoType, rot: Rotation) -> Tetromino;
+
+    /// Gets the origin that a Tetromino of a specified type spawns at in a field of a
+    /// specified width. The y coordinate is relative to the skyline of the field
+    fn spawn_point(&self, tetromino_type: TetrominoType, width: usize) -> Point;
+
+    /// Rotates a Tetromino against a field of locked blocks in a specified direction. Returns
+    /// the rotated Tetromino and the index of the wall kick test that was used
+    fn rotate(&self, field: &Field, tetromino: &Tetromino, dir: Direction) -> Option<(Tetromino, usize)>;
+
+    /// Determines if a T-spin is always a full T-spin when a specified wall kick test
+    /// was needed to rotate the Tetromino into place
+    fn is_full_spin(&self, _dir: Direction, _kick: usize) -> bool {
+        false
+    }
+
+    /// Gets a Tetromino of a specified type in its spawn position, in a field of a
+    /// specified width whose visible rows start at the skyline
+    fn spawn(&self, tetromino_type: TetrominoType, width: usize, skyline: usize) -> Tetromino {
+        let point = self.spawn_point(tetromino_type, width);
+        let origin = Point { x: point.x, y: point.y + skyline as isize };
+        self.tetromino(origin, tetromino_type, Rotation::Spawn)
+    }
+}
+
+/// The names of every RotationSystem that can be created by from_name
+pub const NAMES: [&str; 4] = ["srs", "srs+", "ars", "nrs"];
+
+/// Creates the RotationSystem associated with a name. The 180 degree wall kicks
+/// only apply to the rotation systems that allow 180 degree rotations
+pub fn from_name(name: &str, half_kicks: HalfKicks) -> Option<Box<dyn RotationSystem>> {
+    match name {
+        "srs"  => Some(Box::new(Srs::new(half_kicks))),
+        "srs+" => Some(Box::new(Srs::plus(half_kicks))),
+        "ars"  => Some(Box::new(Ars::new())),
+        "nrs"  => Some(Box::new(Nrs::new())),
+        _ => None,
+    }
+}
+
+/// Gets the Rotation reached by rotating from another Rotation in a specified direction
+pub fn next_rotation(rot: Rotation, dir: Direction) -> Rotation {
+    match dir {
+        Direction::Clockwise => {
+            match rot {
+                Rotation::Spawn => Rotation::Right,
+                Rotation::Right => Rotation::Rot2,
+                Rotation::Rot2  => Rotation::Left,
+                Rotation::Left  => Rotation::Spawn,
+            }
+        },
+
+        Direction::CounterClockwise => {
+            match rot {
+                Rotation::Spawn => Rotation::Left,
+                Rotation::Right => Rotation::Spawn,
+                Rotation::Rot2  => Rotation::Right,
+                Rotation::Left  => Rotation::Rot2,
+            }
+        },
+
+        Direction::Half => {
+            match rot {
+                Rotation::Spawn => Rotation::Rot2,
+                Rotation::Right => Rotation::Left,
+                Rotation::Rot2  => Rotation::Spawn,
+                Rotation::Left  => Rotation::Right,
+            }
+        }
+    }
+}
+
+/// Determines if a Tetromino moved by an (x, y) offset stays within a field
+/// without overlapping any locked blocks
+pub fn fits(field: &Field, tetromino: &Tetromino, offset: Point) -> bool {
+    tetromino.minos().iter().all(|&mino| !is_blocked(field, tetromino.origin() + mino + offset))
+}
+
+/// Determines if a position is outside a field or holds a locked block
+pub fn is_blocked(field: &Field, pos: Point) -> bool {
+    pos.x < 0 || pos.y < 0 || (pos.y as usize) >= field.len() || (pos.x as usize) >= field[pos.y as usize].len()
+        || field[pos.y as usize][pos.x as usize].is_some()
+}
+
+/// Gets the column that a Tetromino with a bounding box of a specified width is spawned
+/// at to be centered in a field, rounding towards the left
+pub fn center(width: usize, box_width: usize) -> isize {
+    (width as isize - box_width as isize) / 2
+}
@@ -0,0 +1,448 @@
+extern crate toml;
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use yatc_rs::board::{Board, FPS};
+
+// The number of entries kept in each table
+pub const TABLE_SIZE: usize = 10;
+
+// The longest name that can be entered
+pub const NAME_LENGTH: usize = 8;
+
+/// A finished game recorded in a high score table
+#[derive(Clone, PartialEq, Debug)]
+pub struct Entry {
+    pub name: String,
+    pub score: usize,
+    pub lines: usize,
+    pub level: usize,
+
+    /// The length of the game in frames
+    pub frames: usize,
+
+    /// The average number of Tetrominos locked every second
+    pub pps: f64,
+
+    pub seed: u64,
+
+    /// The day the game was played on, as YYYY-MM-DD
+    pub date: String,
+}
+
+impl Entry {
+
+    /// Initializes a new Entry struct from the final state of a Board, played today
+    pub fn new(name: &str, board: &Board, seed: u64) -> Self {
+        let pps = if board.frames() > 0 {
+            (board.pieces() as f64 * FPS as f64) / board.frames() as f64
+        } else {
+            0.0
+        };
+
+        Entry {
+            name: String::from(name),
+            score: board.score(),
+            lines: board.cleared(),
+            level: board.level(),
+            frames: board.frames(),
+            pps: pps,
+            seed: seed,
+            date: today(),
+        }
+    }
+
+    /// Determines if the Entry ranks above another. Ties go to the older Entry
+    fn beats(&self, other: &Entry, by_time: bool) -> bool {
+        if by_time {
+            self.frames < other.frames
+        } else {
+            self.score > other.score
+        }
+    }
+
+    /// Parses an Entry from a table in the high score file
+    fn parse(table: &toml::Table) -> Result<Self, String> {
+        let seed = string(table, "seed")?;
+
+        Ok(Entry {
+            name: string(table, "name")?,
+            score: integer(table, "score")?,
+            lines: integer(table, "lines")?,
+            level: integer(table, "level")?,
+            frames: integer(table, "frames")?,
+            pps: table.get("pps").and_then(toml::Value::as_float).ok_or("'pps' must be a float")?,
+            seed: seed.parse().map_err(|_| format!("Invalid seed '{}'", seed))?,
+            date: string(table, "date")?,
+        })
+    }
+
+    /// Converts the Entry into a table for the high score file. The seed is kept as a
+    /// string because TOML integers can't hold every seed
+    fn to_table(&self) -> toml::Table {
+        let mut table = toml::Table::new();
+        table.insert(String::from("name"), toml::Value::from(self.name.as_str()));
+        table.insert(String::from("score"), toml::Value::from(self.score as i64));
+        table.insert(String::from("lines"), toml::Value::from(self.lines as i64));
+        table.insert(String::from("level"), toml::Value::from(self.level as i64));
+        table.insert(String::from("frames"), toml::Value::from(self.frames as i64));
+        table.insert(String::from("pps"), toml::Value::from(self.pps));
+        table.insert(String::from("seed"), toml::Value::from(self.seed.to_string()));
+        table.insert(String::from("date"), toml::Value::from(self.date.as_str()));
+        table
+    }
+}
+
+/// The best games played in each mode, kept in a separate table for every combination
+/// of mode and rules so that only games played the same way are ranked together
+pub struct HighScores {
+    path: Option<PathBuf>,
+    tables: BTreeMap<String, Vec<Entry>>,
+    name: String,
+
+    /// Why the high score file couldn't be loaded, which keeps it from being saved over
+    error: Option<String>,
+}
+
+impl HighScores {
+
+    /// Initializes a new HighScores struct that is never saved
+    pub fn new() -> Self {
+        HighScores {
+            path: None,
+            tables: BTreeMap::new(),
+            name: String::new(),
+            error: None,
+        }
+    }
+
+    /// Initializes a new HighScores struct in place of a high score file that couldn't be
+    /// loaded. It starts empty, and saving it fails instead of replacing the file
+    pub fn unreadable(error: String) -> Self {
+        let mut scores = HighScores::new();
+        scores.error = Some(error);
+        scores
+    }
+
+    /// Loads the HighScores from the high score file in the user's data directory.
+    /// Nothing is loaded if there is no file yet, and nothing is ever saved if the
+    /// data directory can't be found
+    pub fn load() -> Result<Self, String> {
+        let path = match data_path() {
+            Some(path) => path,
+            None => return Ok(HighScores::new()),
+        };
+
+        let mut scores = if path.is_file() {
+            let contents = fs::read_to_string(&path)
+                .map_err(|e| format!("Unable to read {}: {}", path.display(), e))?;
+
+            HighScores::parse(&contents).map_err(|e| format!("{}: {}", path.display(), e))?
+        } else {
+            HighScores::new()
+        };
+
+        scores.path = Some(path);
+        Ok(scores)
+    }
+
+    /// Parses the HighScores from the contents of a high score file
+    pub fn parse(contents: &str) -> Result<Self, String> {
+        let contents = contents.parse::<toml::Table>().map_err(|e| e.to_string())?;
+        let mut scores = HighScores::new();
+
+        for (key, value) in contents.iter() {
+            match key.as_str() {
+                "name" => {
+                    scores.name = String::from(value.as_str().ok_or("'name' must be a string")?);
+                },
+
+                "tables" => {
+                    let tables = value.as_table().ok_or("'tables' must be a table")?;
+
+                    for (name, entries) in tables.iter() {
+                        let entries = entries.as_array()
+                            .ok_or_else(|| format!("The table '{}' must be an array of tables", name))?;
+
+                        let mut parsed = Vec::new();
+
+                        for entry in entries.iter() {
+                            let entry = entry.as_table()
+                                .ok_or_else(|| format!("The table '{}' must be an array of tables", name))?;
+
+                            parsed.push(Entry::parse(entry).map_err(|e| format!("'{}': {}", name, e))?);
+                        }
+
+                        scores.tables.insert(name.clone(), parsed);
+                    }
+                },
+
+                _ => return Err(format!("Unknown key '{}'", key)),
+            }
+        }
+
+        Ok(scores)
+    }
+
+    /// Gets the entries of a table from best to worst
+    pub fn table(&self, table: &str) -> &[Entry] {
+        self.tables.get(table).map_or(&[], |entries| entries.as_slice())
+    }
+
+    /// Gets the position that an Entry would take in a table, if it makes the table
+    pub fn rank(&self, table: &str, entry: &Entry, by_time: bool) -> Option<usize> {
+        let entries = self.table(table);
+        let rank = entries.iter().position(|other| entry.beats(other, by_time)).unwrap_or(entries.len());
+
+        if rank < TABLE_SIZE { Some(rank) } else { None }
+    }
+
+    /// Adds an Entry to a table, dropping the worst entry once the table is full.
+    /// Returns the position of the Entry, if it made the table
+    pub fn insert(&mut self, table: &str, entry: Entry, by_time: bool) -> Option<usize> {
+        let rank = self.rank(table, &entry, by_time)?;
+        let entries = self.tables.entry(String::from(table)).or_default();
+
+        entries.insert(rank, entry);
+        entries.truncate(TABLE_SIZE);
+        Some(rank)
+    }
+
+    /// Writes the HighScores to the high score file. The file is replaced in a single
+    /// step, so that a crash while saving leaves the previous scores intact
+    pub fn save(&self) -> Result<(), String> {
+        if let Some(ref error) = self.error {
+            return Err(error.clone());
+        }
+
+        let path = match self.path {
+            Some(ref path) => path,
+            None => return Ok(()),
+        };
+
+        let mut tables = toml::Table::new();
+
+        for (name, entries) in self.tables.iter() {
+            let entries = entries.iter().map(|entry| toml::Value::Table(entry.to_table())).collect();
+            tables.insert(name.clone(), toml::Value::Array(entries));
+        }
+
+        let mut contents = toml::Table::new();
+        contents.insert(String::from("name"), toml::Value::from(self.name.as_str()));
+        contents.insert(String::from("tables"), toml::Value::Table(tables));
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("Unable to create {}: {}", dir.display(), e))?;
+        }
+
+        // Write to a temporary file next to the real one, then rename it over the top
+        let temp = path.with_extension("toml.tmp");
+
+        let write = || -> std::io::Result<()> {
+            let mut file = fs::File::create(&temp)?;
+            file.write_all(contents.to_string().as_bytes())?;
+            file.sync_all()?;
+            fs::rename(&temp, path)
+        };
+
+        write().map_err(|e| {
+            let _ = fs::remove_file(&temp);
+            format!("Unable to write {}: {}", path.display(), e)
+        })
+    }
+
+    // GETTERS / SETTERS
+
+    /// Gets the name entered for the last high score, offered again for the next one
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn set_name(&mut self, name: &str) {
+        self.name = String::from(name);
+    }
+}
+
+impl Default for HighScores {
+    fn default() -> Self {
+        HighScores::new()
+    }
+}
+
+/// Gets the directory that the game keeps its data in within the user's data directory,
+/// following the XDG base directory specification
+pub fn data_dir() -> Option<PathBuf> {
+    let dir = match env::var_os("XDG_DATA_HOME") {
+        Some(ref dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(env::var_os("HOME")?).join(".local").join("share"),
+    };
+
+    Some(dir.join("yatc-rs"))
+}
+
+/// Gets the path of the high score file in the user's data directory
+pub fn data_path() -> Option<PathBuf> {
+    Some(data_dir()?.join("scores.toml"))
+}
+
+/// Determines if a character can be part of a name
+pub fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.'
+}
+
+/// Gets a string from a table in the high score file
+fn string(table: &toml::Table, key: &str) -> Result<String, String> {
+    table.get(key)
+        .and_then(toml::Value::as_str)
+        .map(String::from)
+        .ok_or_else(|| format!("'{}' must be a string", key))
+}
+
+/// Gets a non-negative integer from a table in the high score file
+fn integer(table: &toml::Table, key: &str) -> Result<usize, String> {
+    match table.get(key).and_then(toml::Value::as_integer) {
+        Some(value) if value >= 0 => Ok(value as usize),
+        _ => Err(format!("'{}' must be a non-negative integer", key)),
+    }
+}
+
+/// Gets today's date in UTC as YYYY-MM-DD
+fn today() -> String {
+    date(SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.as_secs()))
+}
+
+/// Gets the date in UTC as YYYY-MM-DD of a number of seconds since the Unix epoch
+fn date(seconds: u64) -> String {
+    let days = (seconds / 86_400) as i64;
+
+    // Convert days since 1970-01-01 into a civil date, counting from 0000-03-01 so that
+    // leap days fall at the end of each year
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let month = if month < 10 { month + 3 } else { month - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Gets an Entry with a score and a length in frames
+    fn entry(name: &str, score: usize, frames: usize) -> Entry {
+        Entry {
+            name: String::from(name),
+            score: score,
+            lines: 40,
+            level: 4,
+            frames: frames,
+            pps: 2.5,
+            seed: u64::MAX,
+            date: String::from("2024-12-31"),
+        }
+    }
+
+    #[test]
+    fn unreadable_file_is_not_saved() {
+        assert_eq!(HighScores::new().save(), Ok(()));
+
+        let mut scores = HighScores::unreadable(String::from("scores.toml: Unknown key 'x'"));
+        scores.insert("marathon", entry("AAA", 100, 600), false);
+
+        assert_eq!(scores.table("marathon").len(), 1);
+        assert_eq!(scores.save(), Err(String::from("scores.toml: Unknown key 'x'")));
+    }
+
+    #[test]
+    fn parse_tables() {
+        let scores = HighScores::parse(r#"
+            name = "ALICE"
+
+            [[tables.sprint]]
+            name = "BOB"
+            score = 1200
+            lines = 40
+            level = 4
+            frames = 3600
+            pps = 2.5
+            seed = "18446744073709551615"
+            date = "2024-12-31"
+        "#).unwrap();
+
+        assert_eq!(scores.name(), "ALICE");
+        assert_eq!(scores.table("sprint"), [entry("BOB", 1200, 3600)]);
+        assert!(scores.table("ultra").is_empty());
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(HighScores::parse("colour = 1").err().as_deref(), Some("Unknown key 'colour'"));
+        assert_eq!(HighScores::parse("[[tables.sprint]]\nseed = \"1\"\nname = \"BOB\"\nscore = -5").err().as_deref(),
+                   Some("'sprint': 'score' must be a non-negative integer"));
+        assert!(HighScores::parse("name = ").is_err());
+    }
+
+    #[test]
+    fn rank_by_score() {
+        let mut scores = HighScores::new();
+        assert_eq!(scores.insert("ultra", entry("A", 100, 0), false), Some(0));
+        assert_eq!(scores.insert("ultra", entry("B", 300, 0), false), Some(0));
+        assert_eq!(scores.insert("ultra", entry("C", 200, 0), false), Some(1));
+
+        // Ties go to the entry that was there first
+        assert_eq!(scores.rank("ultra", &entry("D", 200, 0), false), Some(2));
+
+        let names = scores.table("ultra").iter().map(|entry| entry.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, ["B", "C", "A"]);
+    }
+
+    #[test]
+    fn rank_by_time() {
+        let mut scores = HighScores::new();
+        assert_eq!(scores.insert("sprint", entry("A", 0, 3000), true), Some(0));
+        assert_eq!(scores.insert("sprint", entry("B", 0, 2000), true), Some(0));
+        assert_eq!(scores.insert("sprint", entry("C", 900, 4000), true), Some(2));
+
+        let names = scores.table("sprint").iter().map(|entry| entry.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, ["B", "A", "C"]);
+    }
+
+    #[test]
+    fn insert_into_full_table() {
+        let mut scores = HighScores::new();
+
+        for i in 0..TABLE_SIZE {
+            scores.insert("ultra", entry("A", (i + 1) * 100, 0), false);
+        }
+
+        assert_eq!(scores.rank("ultra", &entry("B", 50, 0), false), None);
+        assert_eq!(scores.insert("ultra", entry("B", 50, 0), false), None);
+        assert_eq!(scores.insert("ultra", entry("C", 150, 0), false), Some(TABLE_SIZE - 1));
+
+        let table = scores.table("ultra");
+        assert_eq!(table.len(), TABLE_SIZE);
+        assert_eq!(table[0].score, TABLE_SIZE * 100);
+        assert_eq!(table[TABLE_SIZE - 1].name, "C");
+    }
+
+    #[test]
+    fn dates() {
+        assert_eq!(date(0), "1970-01-01");
+        assert_eq!(date(951_782_400), "2000-02-29");
+        assert_eq!(date(951_868_800), "2000-03-01");
+        assert_eq!(date(1_735_603_200 + 86_399), "2024-12-31");
+        assert_eq!(date(4_107_542_400), "2100-03-01");
+        assert_eq!(today().len(), 10);
+    }
+}
@@ -0,0 +1,105 @@
+/// The kinds of T-spin recognized when a T Tetromino locks
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Spin {
+    None,
+    Mini,
+    Full,
+}
+
+/// A classification of a locked Tetromino by the lines it cleared and how it got into place
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Clear {
+    pub lines: usize,
+    pub spin: Spin,
+    pub back_to_back: bool,
+    pub perfect_clear: bool,
+}
+
+/// Calculates the bonus points awarded for a combo at a given level
+pub fn combo_points(combo: usize, level: usize) -> usize {
+    50 * combo * (level + 1)
+}
+
+impl Clear {
+
+    /// Initializes a new Clear struct
+    pub fn new(lines: usize, spin: Spin) -> Self {
+        Clear {
+            lines: lines,
+            spin: spin,
+            back_to_back: false,
+            perfect_clear: false,
+        }
+    }
+
+    /// Determines if the clear is difficult enough to continue a back-to-back chain
+    pub fn is_difficult(&self) -> bool {
+        self.lines == 4 || (self.lines > 0 && self.spin != Spin::None)
+    }
+
+    /// Determines if the clear is worth any points
+    pub fn is_scoring(&self) -> bool {
+        self.lines > 0 || self.spin != Spin::None
+    }
+
+    /// Calculates the points awarded for the clear at a given level
+    pub fn points(&self, level: usize) -> usize {
+        let base = match (self.spin, self.lines) {
+
+            // Every clear uses the guideline scoring table
+            (Spin::None, 1) =>  100,
+            (Spin::None, 2) =>  300,
+            (Spin::None, 3) =>  500,
+            (Spin::None, 4) =>  800,
+            (Spin::Mini, 0) =>  100,
+            (Spin::Mini, 1) =>  200,
+            (Spin::Mini, 2) =>  400,
+            (Spin::Full, 0) =>  400,
+            (Spin::Full, 1) =>  800,
+            (Spin::Full, 2) => 1200,
+            (Spin::Full, 3) => 1600,
+
+            // Any other combination is impossible in the current system
+            _ => 0,
+        };
+
+        // Back-to-back clears are worth one and a half times as much
+        let base = if self.back_to_back { base * 3 / 2 } else { base };
+
+        (base + self.perfect_clear_bonus()) * (level + 1)
+    }
+
+    /// Gets the bonus awarded for leaving the field empty
+    fn perfect_clear_bonus(&self) -> usize {
+        if !self.perfect_clear {
+            return 0;
+        }
+
+        match (self.lines, self.back_to_back) {
+            (1, _)    =>  800,
+            (2, _)    => 1200,
+            (3, _)    => 1800,
+            (4, true) => 3200,
+            (4, _)    => 2000,
+            _ => 0,
+        }
+    }
+
+    /// Gets the name of the clear as shown to the player
+    pub fn name(&self) -> &'static str {
+        match (self.spin, self.lines) {
+            (Spin::None, 1) => "SINGLE",
+            (Spin::None, 2) => "DOUBLE",
+            (Spin::None, 3) => "TRIPLE",
+            (Spin::None, 4) => "TETRIS",
+            (Spin::Mini, 0) => "T-SPIN MINI",
+            (Spin::Mini, 1) => "T-SPIN MINI SINGLE",
+            (Spin::Mini, _) => "T-SPIN MINI DOUBLE",
+            (Spin::Full, 0) => "T-SPIN",
+            (Spin::Full, 1) => "T-SPIN SINGLE",
+            (Spin::Full, 2) => "T-SPIN DOUBLE",
+            (Spin::Full, _) => "T-SPIN TRIPLE",
+            _ => "",
+        }
+    }
+}
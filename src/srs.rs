@@ -1,138 +1,124 @@
-use super::board::{Field, HEIGHT, WIDTH};
+use super::board::Field;
+use super::rotation;
+use super::rotation::{Direction, RotationSystem};
 use super::tetromino::{
     Point,
-    Rotation, 
-    Tetromino, 
+    Rotation,
+    Tetromino,
     TetrominoType,
 };
 
-#[derive(Copy, Clone, Debug)]
-pub enum Direction {
-    Clockwise,
-    CounterClockwise,
-}
-
-/// Rotates a Tetromino located inside a field in a specified direction
-pub fn rotate(field: &Field, tetromino: &Tetromino, dir: Direction) -> Option<(Field, Tetromino)> {
-    let mut field = field.clone();
-
-    // Remove the original piece from the field. Since we cloned the field,
-    // we are able to freely manipulate it without worry. Assuming the
-    // rotation is possible, this will make performing it easier later on
-    erase(&mut field, &tetromino);
+/// The wall kicks tested when a Tetromino is rotated by 180 degrees, which
+/// the Super Rotation System itself does not define
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum HalfKicks {
 
-    // Rotate the piece if possible
-    if let Some(rotated) = is_rotatable(&field, &tetromino, dir) {
-        do_rotation(&mut field, &rotated);
-        Some((field, rotated))
-    }
+    /// The wall kicks used by SRS+ in TETR.IO
+    Tetrio,
 
-    else {
-        None
-    }
+    /// No wall kicks, so the Tetromino can only rotate in place
+    None,
 }
 
-/// Erases a Tetromino from a field
-fn erase(field: &mut Field, tetromino: &Tetromino) {
-    for &mino in tetromino.minos().iter() {
-        let pos = tetromino.origin() + mino;
-        field[pos.y as usize][pos.x as usize] = None;
-    }
+/// The Super Rotation System used by guideline games, along with the SRS+ variant
+/// whose I Tetromino wall kicks are symmetric
+pub struct Srs {
+    kicks_i: &'static WallKicks,
+    half_kicks: HalfKicks,
 }
 
-/// Determines if a Tetromino is rotatable within a specified field. Returns the rotated
-/// Tetromino if the rotation is possible
-fn is_rotatable(field: &Field, tetromino: &Tetromino, dir: Direction) -> Option<Tetromino> {
-    let rotated = next_rotated_tetromino(tetromino, dir);
+impl Srs {
 
-    for &mino in rotated.minos().iter() {
-        let pos = tetromino.origin() + mino;
+    /// Initializes a new Srs struct using the standard wall kicks
+    pub fn new(half_kicks: HalfKicks) -> Self {
+        Srs {
+            kicks_i: &WALL_KICKS_I,
+            half_kicks: half_kicks,
+        }
+    }
 
-        // Determine if it is possible for the rotated mino to move to the new position.
-        // If the new position is outside the bounds or is already occupied, then it is
-        // not possible and we need to test for wall kicks
-        if pos.x < 0 || pos.y < 0 || pos.x as usize >= WIDTH || pos.y as usize >= HEIGHT
-                || field[pos.y as usize][pos.x as usize].is_some() {
-            return wall_kick(field, &rotated, dir);
+    /// Initializes a new Srs struct using the symmetric I Tetromino wall kicks of SRS+
+    pub fn plus(half_kicks: HalfKicks) -> Self {
+        Srs {
+            kicks_i: &WALL_KICKS_I_PLUS,
+            half_kicks: half_kicks,
         }
     }
 
-    Some(rotated)
-}
+    /// Attempts to wall kick a rotated Tetromino, using the wall kick tests
+    /// for the Rotation that it was rotated from
+    fn wall_kick(&self, field: &Field, tetromino: &Tetromino, rotated: &Tetromino, dir: Direction) -> Option<(Tetromino, usize)> {
+        let rot = tetromino.rot() as usize;
+
+        let tests: &[Point] = match (dir, self.half_kicks) {
+            (Direction::Half, HalfKicks::Tetrio) => &HALF_WALL_KICKS[rot],
+            (Direction::Half, HalfKicks::None) => &[],
+
+            _ => match tetromino.tetromino_type() {
+                TetrominoType::I => &self.kicks_i[rot][dir as usize],
+                _ => &WALL_KICKS[rot][dir as usize],
+            },
+        };
+
+        for (i, &test) in tests.iter().enumerate() {
+            if rotation::fits(field, rotated, test) {
+                let mut kicked = *rotated;
+                kicked.set_origin(rotated.origin() + test);
+                return Some((kicked, i));
+            }
+        }
 
-/// Attempts to wall kick a Tetromino
-fn wall_kick(field: &Field, tetromino: &Tetromino, dir: Direction) -> Option<Tetromino> {
-    let tests = match tetromino.tetromino_type() {
-        TetrominoType::I => WALL_KICKS_I[tetromino.rot() as usize][dir as usize].clone(),
-        _ => WALL_KICKS[tetromino.rot() as usize][dir as usize].clone(),
-    };
+        None
+    }
+}
 
-    for &test in tests.iter() {
-        if let Some(kicked) = test_wall_kick(field, tetromino, test) {
-            return Some(kicked);
-        }
+impl RotationSystem for Srs {
+    fn tetromino(&self, origin: Point, tetromino_type: TetrominoType, rot: Rotation) -> Tetromino {
+        Tetromino::new(origin, tetromino_type, rot)
     }
 
-    None
-}
+    fn spawn_point(&self, _tetromino_type: TetrominoType, width: usize) -> Point {
+        Point { x: rotation::center(width, BOX_WIDTH), y: SPAWN_ROW }
+    }
 
-/// Performs a wall kick test
-fn test_wall_kick(field: &Field, tetromino: &Tetromino, test: Point) -> Option<Tetromino> {
-     for &mino in tetromino.minos().iter() {
-        let pos = tetromino.origin() + mino + test;
+    fn rotate(&self, field: &Field, tetromino: &Tetromino, dir: Direction) -> Option<(Tetromino, usize)> {
+        let rot = rotation::next_rotation(tetromino.rot(), dir);
+        let rotated = self.tetromino(tetromino.origin(), tetromino.tetromino_type(), rot);
 
-        if pos.x < 0 || pos.y < 0 || (pos.x as usize) >= WIDTH || (pos.y as usize) >= HEIGHT
-                || field[pos.y as usize][pos.x  as usize].is_some() {
-            return None;
+        // If the rotated Tetromino is outside the bounds or overlaps the
+        // stack, then we need to test for wall kicks
+        if rotation::fits(field, &rotated, Point { x: 0, y: 0 }) {
+            Some((rotated, 0))
+        } else {
+            self.wall_kick(field, tetromino, &rotated, dir)
         }
     }
 
-    let mut tetromino = tetromino.clone();
-    let new = tetromino.origin() + test;
-    tetromino.set_origin(new);
-    Some(tetromino)
-}
-
-/// Performs the actual rotation
-fn do_rotation(field: &mut Field, rotated: &Tetromino) {
-    for &mino in rotated.minos().iter() {
-        let pos = rotated.origin() + mino;
-        field[pos.y as usize][pos.x as usize] = Some(rotated.tetromino_type());
+    fn is_full_spin(&self, dir: Direction, kick: usize) -> bool {
+        dir != Direction::Half && kick == LAST_KICK
     }
 }
 
-/// Gets the next rotation of a Tetromino in a specified direction
-fn next_rotated_tetromino(tetromino: &Tetromino, dir: Direction) -> Tetromino {
-    let rot = match dir {
-        Direction::Clockwise => {
-            match tetromino.rot() {
-                Rotation::Spawn => Rotation::Right,
-                Rotation::Right => Rotation::Rot2,
-                Rotation::Rot2  => Rotation::Left,
-                Rotation::Left  => Rotation::Spawn,
-            }
-        },
-
-        Direction::CounterClockwise => {
-            match tetromino.rot() {
-                Rotation::Spawn => Rotation::Left,
-                Rotation::Right => Rotation::Spawn,
-                Rotation::Rot2  => Rotation::Right,
-                Rotation::Left  => Rotation::Rot2,
-            }
-        }
-    };
-
-    Tetromino::new(tetromino.origin(), tetromino.tetromino_type(), rot)
-}
+// Tetrominos spawn in a 4x4 bounding box whose top is two rows above the skyline
+const BOX_WIDTH: usize = 4;
+const SPAWN_ROW: isize = -2;
 
 const TESTS: usize = 5;
+const HALF_TESTS: usize = 6;
+
+/// The index of the final wall kick test of a quarter rotation. Using it always
+/// results in a full T-spin
+pub const LAST_KICK: usize = TESTS - 1;
 const DIRECTIONS: usize = 2;
 const ROTS: usize = 4;
 
+// Wall kick data is indexed by the Rotation being rotated from, then the Direction
+type WallKicks = [[[Point; TESTS]; DIRECTIONS]; ROTS];
+
 // Wall kick data as specified by the Super Rotation System
-const WALL_KICKS: [[[Point; TESTS]; DIRECTIONS]; ROTS] = [SPAWN_WK, RIGHT_WK, ROT2_WK, LEFT_WK];
-const WALL_KICKS_I: [[[Point; TESTS]; DIRECTIONS]; ROTS] = [SPAWN_WK_I, RIGHT_WK_I, ROT2_WK_I, LEFT_WK_I];
+const WALL_KICKS: WallKicks = [SPAWN_WK, RIGHT_WK, ROT2_WK, LEFT_WK];
+const WALL_KICKS_I: WallKicks = [SPAWN_WK_I, RIGHT_WK_I, ROT2_WK_I, LEFT_WK_I];
+const WALL_KICKS_I_PLUS: WallKicks = [SPAWN_WK_I_PLUS, RIGHT_WK_I_PLUS, ROT2_WK_I_PLUS, LEFT_WK_I_PLUS];
 
 // The wall kick data for J, L, O, S, T, and Z
 const SPAWN_WK: [[Point; TESTS]; DIRECTIONS] = [
@@ -141,10 +127,10 @@ const SPAWN_WK: [[Point; TESTS]; DIRECTIONS] = [
         Point { x: -1, y:  0 },
         Point { x: -1, y: -1 },
         Point { x:  0, y:  2 },
-        Point { x: -1, y:  2 }, 
-    ], 
+        Point { x: -1, y:  2 },
+    ],
 
-    [   
+    [
         Point { x:  0, y:  0 },
         Point { x:  1, y:  0 },
         Point { x:  1, y: -1 },
@@ -159,10 +145,10 @@ const RIGHT_WK: [[Point; TESTS]; DIRECTIONS] = [
         Point { x:  1, y:  0 },
         Point { x:  1, y:  1 },
         Point { x:  0, y: -2 },
-        Point { x:  1, y: -2 }, 
-    ], 
+        Point { x:  1, y: -2 },
+    ],
 
-    [   
+    [
         Point { x:  0, y:  0 },
         Point { x:  1, y:  0 },
         Point { x:  1, y:  1 },
@@ -177,10 +163,10 @@ const ROT2_WK: [[Point; TESTS]; DIRECTIONS] = [
         Point { x:  1, y:  0 },
         Point { x:  1, y: -1 },
         Point { x:  0, y:  2 },
-        Point { x:  1, y:  2 }, 
-    ], 
+        Point { x:  1, y:  2 },
+    ],
 
-    [   
+    [
         Point { x:  0, y:  0 },
         Point { x: -1, y:  0 },
         Point { x: -1, y: -1 },
@@ -195,14 +181,14 @@ const LEFT_WK: [[Point; TESTS]; DIRECTIONS] = [
         Point { x: -1, y:  0 },
         Point { x: -1, y:  1 },
         Point { x:  0, y: -2 },
-        Point { x: -1, y: -2 }, 
-    ], 
+        Point { x: -1, y: -2 },
+    ],
 
-    [   
+    [
         Point { x:  0, y:  0 },
         Point { x: -1, y:  0 },
         Point { x: -1, y:  1 },
-        Point { x:  0, y:  2 },
+        Point { x:  0, y: -2 },
         Point { x: -1, y: -2 },
     ]
 ];
@@ -214,10 +200,10 @@ const SPAWN_WK_I: [[Point; TESTS]; DIRECTIONS] = [
         Point { x: -2, y:  0 },
         Point { x:  1, y:  0 },
         Point { x: -2, y:  1 },
-        Point { x:  1, y: -2 }, 
-    ], 
+        Point { x:  1, y: -2 },
+    ],
 
-    [   
+    [
         Point { x:  0, y:  0 },
         Point { x: -1, y:  0 },
         Point { x:  2, y:  0 },
@@ -232,14 +218,14 @@ const RIGHT_WK_I: [[Point; TESTS]; DIRECTIONS] = [
         Point { x: -1, y:  0 },
         Point { x:  2, y:  0 },
         Point { x: -1, y: -2 },
-        Point { x:  2, y:  1 }, 
-    ], 
+        Point { x:  2, y:  1 },
+    ],
 
-    [   
+    [
         Point { x:  0, y:  0 },
         Point { x:  2, y:  0 },
         Point { x: -1, y:  0 },
-        Point { x:  2, y:  1 },
+        Point { x:  2, y: -1 },
         Point { x: -1, y:  2 },
     ]
 ];
@@ -250,10 +236,10 @@ const ROT2_WK_I: [[Point; TESTS]; DIRECTIONS] = [
         Point { x:  2, y:  0 },
         Point { x: -1, y:  0 },
         Point { x:  2, y: -1 },
-        Point { x: -1, y:  2 }, 
-    ], 
+        Point { x: -1, y:  2 },
+    ],
 
-    [   
+    [
         Point { x:  0, y:  0 },
         Point { x:  1, y:  0 },
         Point { x: -2, y:  0 },
@@ -268,14 +254,246 @@ const LEFT_WK_I: [[Point; TESTS]; DIRECTIONS] = [
         Point { x:  1, y:  0 },
         Point { x: -2, y:  0 },
         Point { x:  1, y:  2 },
-        Point { x: -2, y: -1 }, 
-    ], 
+        Point { x: -2, y: -1 },
+    ],
+
+    [
+        Point { x:  0, y:  0 },
+        Point { x: -2, y:  0 },
+        Point { x:  1, y:  0 },
+        Point { x: -2, y:  1 },
+        Point { x:  1, y: -2 },
+    ]
+];
+
+// The symmetric wall kick data for I used by SRS+
+const SPAWN_WK_I_PLUS: [[Point; TESTS]; DIRECTIONS] = [
+    [
+        Point { x:  0, y:  0 },
+        Point { x:  1, y:  0 },
+        Point { x: -2, y:  0 },
+        Point { x: -2, y:  1 },
+        Point { x:  1, y: -2 },
+    ],
+
+    [
+        Point { x:  0, y:  0 },
+        Point { x: -1, y:  0 },
+        Point { x:  2, y:  0 },
+        Point { x:  2, y:  1 },
+        Point { x: -1, y: -2 },
+    ]
+];
 
-    [   
+const RIGHT_WK_I_PLUS: [[Point; TESTS]; DIRECTIONS] = [
+    [
         Point { x:  0, y:  0 },
         Point { x: -1, y:  0 },
         Point { x:  2, y:  0 },
         Point { x: -1, y: -2 },
         Point { x:  2, y:  1 },
+    ],
+
+    [
+        Point { x:  0, y:  0 },
+        Point { x: -1, y:  0 },
+        Point { x:  2, y:  0 },
+        Point { x: -1, y:  2 },
+        Point { x:  2, y: -1 },
+    ]
+];
+
+const ROT2_WK_I_PLUS: [[Point; TESTS]; DIRECTIONS] = [
+    [
+        Point { x:  0, y:  0 },
+        Point { x:  2, y:  0 },
+        Point { x: -1, y:  0 },
+        Point { x:  2, y: -1 },
+        Point { x: -1, y:  2 },
+    ],
+
+    [
+        Point { x:  0, y:  0 },
+        Point { x: -2, y:  0 },
+        Point { x:  1, y:  0 },
+        Point { x: -2, y: -1 },
+        Point { x:  1, y:  2 },
+    ]
+];
+
+const LEFT_WK_I_PLUS: [[Point; TESTS]; DIRECTIONS] = [
+    [
+        Point { x:  0, y:  0 },
+        Point { x:  1, y:  0 },
+        Point { x: -2, y:  0 },
+        Point { x:  1, y:  2 },
+        Point { x: -2, y: -1 },
+    ],
+
+    [
+        Point { x:  0, y:  0 },
+        Point { x:  1, y:  0 },
+        Point { x: -2, y:  0 },
+        Point { x:  1, y: -2 },
+        Point { x: -2, y:  1 },
     ]
-];
\ No newline at end of file
+];
+
+// The 180 degree wall kick data used by TETR.IO, which is shared by every Tetromino
+const HALF_WALL_KICKS: [[Point; HALF_TESTS]; ROTS] = [
+    [
+        Point { x:  0, y:  0 },
+        Point { x:  0, y: -1 },
+        Point { x:  1, y: -1 },
+        Point { x: -1, y: -1 },
+        Point { x:  1, y:  0 },
+        Point { x: -1, y:  0 },
+    ],
+
+    [
+        Point { x:  0, y:  0 },
+        Point { x:  1, y:  0 },
+        Point { x:  1, y: -2 },
+        Point { x:  1, y: -1 },
+        Point { x:  0, y: -2 },
+        Point { x:  0, y: -1 },
+    ],
+
+    [
+        Point { x:  0, y:  0 },
+        Point { x:  0, y:  1 },
+        Point { x: -1, y:  1 },
+        Point { x:  1, y:  1 },
+        Point { x: -1, y:  0 },
+        Point { x:  1, y:  0 },
+    ],
+
+    [
+        Point { x:  0, y:  0 },
+        Point { x: -1, y:  0 },
+        Point { x: -1, y: -2 },
+        Point { x: -1, y: -1 },
+        Point { x:  0, y: -2 },
+        Point { x:  0, y: -1 },
+    ],
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::board::Field;
+
+    const TYPES: [TetrominoType; 7] = [
+        TetrominoType::I,
+        TetrominoType::J,
+        TetrominoType::L,
+        TetrominoType::O,
+        TetrominoType::S,
+        TetrominoType::T,
+        TetrominoType::Z,
+    ];
+
+    const ROTATIONS: [Rotation; ROTS] = [Rotation::Spawn, Rotation::Right, Rotation::Rot2, Rotation::Left];
+
+    // Far enough from the walls that every wall kick stays inside the field
+    const ORIGIN: Point = Point { x: 3, y: 10 };
+
+    /// Gets a field that is filled everywhere except under a Tetromino
+    fn filled_except(tetromino: &Tetromino) -> Field {
+        let mut field = vec![vec![Some(TetrominoType::Z); 10]; 22];
+
+        for &mino in tetromino.minos().iter() {
+            let pos = tetromino.origin() + mino;
+            field[pos.y as usize][pos.x as usize] = None;
+        }
+
+        field
+    }
+
+    /// Gets the Rotation that mirrors another across the vertical axis
+    fn mirror(rot: Rotation) -> Rotation {
+        match rot {
+            Rotation::Right => Rotation::Left,
+            Rotation::Left => Rotation::Right,
+            rot => rot,
+        }
+    }
+
+    #[test]
+    fn plus_i_kicks_are_symmetric() {
+
+        // Mirroring a rotation swaps its direction, and mirrors each of its wall kicks
+        for &rot in ROTATIONS.iter() {
+            for &(dir, opposite) in [(Direction::Clockwise, Direction::CounterClockwise),
+                                     (Direction::CounterClockwise, Direction::Clockwise)].iter() {
+                let kicks = WALL_KICKS_I_PLUS[rot as usize][dir as usize];
+                let mirrored = WALL_KICKS_I_PLUS[mirror(rot) as usize][opposite as usize];
+
+                for (kick, other) in kicks.iter().zip(mirrored.iter()) {
+                    assert_eq!(Point { x: -kick.x, y: kick.y }, *other, "{:?} {:?}", rot, dir);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn half_rotation_in_open_field() {
+        let field = vec![vec![None; 10]; 22];
+
+        for kicks in [HalfKicks::Tetrio, HalfKicks::None].iter() {
+            let srs = Srs::new(*kicks);
+
+            for &tetromino_type in TYPES.iter() {
+                for &rot in ROTATIONS.iter() {
+                    let tetromino = Tetromino::new(ORIGIN, tetromino_type, rot);
+                    let (rotated, kick) = srs.rotate(&field, &tetromino, Direction::Half).unwrap();
+
+                    assert_eq!(rotated.rot(), rotation::next_rotation(rot, Direction::Half));
+                    assert_eq!(rotated.origin(), ORIGIN);
+                    assert_eq!(kick, 0);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn half_rotation_takes_each_kick() {
+        let srs = Srs::new(HalfKicks::Tetrio);
+
+        for &tetromino_type in TYPES.iter() {
+            for &rot in ROTATIONS.iter() {
+                let tetromino = Tetromino::new(ORIGIN, tetromino_type, rot);
+                let next = rotation::next_rotation(rot, Direction::Half);
+
+                // Leave room for the rotated Tetromino only at the kick being tested,
+                // so that every test before it is blocked
+                for (i, &test) in HALF_WALL_KICKS[rot as usize].iter().enumerate() {
+                    let kicked = Tetromino::new(ORIGIN + test, tetromino_type, next);
+                    let field = filled_except(&kicked);
+
+                    assert_eq!(srs.rotate(&field, &tetromino, Direction::Half), Some((kicked, i)),
+                               "{:?} from {:?}", tetromino_type, rot);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn half_rotation_without_kicks() {
+        let srs = Srs::new(HalfKicks::None);
+
+        for &tetromino_type in TYPES.iter() {
+            for &rot in ROTATIONS.iter() {
+                let tetromino = Tetromino::new(ORIGIN, tetromino_type, rot);
+                let next = rotation::next_rotation(rot, Direction::Half);
+
+                // The first TETR.IO kick fits, but it must not be tried
+                let kicked = Tetromino::new(ORIGIN + HALF_WALL_KICKS[rot as usize][1], tetromino_type, next);
+                let field = filled_except(&kicked);
+
+                assert_eq!(srs.rotate(&field, &tetromino, Direction::Half), None,
+                           "{:?} from {:?}", tetromino_type, rot);
+            }
+        }
+    }
+}
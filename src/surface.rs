@@ -0,0 +1,300 @@
+#[cfg(feature = "rustbox")]
+extern crate rustbox;
+
+#[cfg(feature = "crossterm")]
+extern crate crossterm;
+
+#[cfg(any(test, feature = "crossterm"))]
+use std::cell::RefCell;
+
+#[cfg(feature = "crossterm")]
+use std::io::{self, BufWriter, Stdout, Write};
+
+#[cfg(feature = "rustbox")]
+use self::rustbox::RustBox;
+
+#[cfg(feature = "crossterm")]
+use self::crossterm::{cursor, event, style, terminal, QueueableCommand};
+
+/// The colors that can be drawn onto a Surface
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Color {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+}
+
+/// The text styles that can be drawn onto a Surface
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Style {
+    Normal,
+    Bold,
+}
+
+/// A grid of characters that can be drawn onto, such as a terminal.
+///
+/// The origin of a Surface is (0, 0) and is located at the top left
+pub trait Surface {
+
+    /// Prints a character at an (x, y) position
+    fn print_char(&self, x: usize, y: usize, style: Style, fg: Color, bg: Color, c: char);
+
+    /// Prints a string at an (x, y) position
+    fn print(&self, x: usize, y: usize, style: Style, fg: Color, bg: Color, s: &str) {
+        for (i, c) in s.chars().enumerate() {
+            self.print_char(x + i, y, style, fg, bg, c);
+        }
+    }
+
+    /// Erases everything that has been printed
+    fn clear(&self);
+
+    /// Shows everything printed since the last call
+    fn present(&self);
+}
+
+/// A single character drawn onto a Buffer
+#[cfg(test)]
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Cell {
+    pub c: char,
+    pub style: Style,
+    pub fg: Color,
+    pub bg: Color,
+}
+
+#[cfg(test)]
+const BLANK: Cell = Cell {
+    c: ' ',
+    style: Style::Normal,
+    fg: Color::White,
+    bg: Color::Black,
+};
+
+/// An in-memory Surface, which allows the user interface to be drawn and checked
+/// in tests without a terminal
+#[cfg(test)]
+pub struct Buffer {
+    w: usize,
+    h: usize,
+    cells: RefCell<Vec<Cell>>,
+}
+
+#[cfg(test)]
+impl Buffer {
+
+    /// Initializes a new blank Buffer struct with a specified width and height
+    pub fn new(w: usize, h: usize) -> Self {
+        Buffer {
+            w: w,
+            h: h,
+            cells: RefCell::new(vec![BLANK; w * h]),
+        }
+    }
+
+    /// Gets the Cell at an (x, y) position
+    pub fn cell(&self, x: usize, y: usize) -> Option<Cell> {
+        if x < self.w && y < self.h {
+            Some(self.cells.borrow()[(y * self.w) + x])
+        } else {
+            None
+        }
+    }
+
+    /// Gets the characters of each row, with any trailing whitespace removed
+    pub fn lines(&self) -> Vec<String> {
+        self.cells.borrow()
+            .chunks(self.w)
+            .map(|row| row.iter().map(|cell| cell.c).collect::<String>().trim_end().to_string())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+impl Surface for Buffer {
+    fn print_char(&self, x: usize, y: usize, style: Style, fg: Color, bg: Color, c: char) {
+        if x < self.w && y < self.h {
+            self.cells.borrow_mut()[(y * self.w) + x] = Cell {
+                c: c,
+                style: style,
+                fg: fg,
+                bg: bg,
+            };
+        }
+    }
+
+    fn clear(&self) {
+        for cell in self.cells.borrow_mut().iter_mut() {
+            *cell = BLANK;
+        }
+    }
+
+    fn present(&self) { }
+}
+
+#[cfg(feature = "rustbox")]
+impl Surface for RustBox {
+    fn print_char(&self, x: usize, y: usize, style: Style, fg: Color, bg: Color, c: char) {
+        RustBox::print_char(self, x, y, rustbox_style(style), rustbox_color(fg), rustbox_color(bg), c);
+    }
+
+    fn print(&self, x: usize, y: usize, style: Style, fg: Color, bg: Color, s: &str) {
+        RustBox::print(self, x, y, rustbox_style(style), rustbox_color(fg), rustbox_color(bg), s);
+    }
+
+    fn clear(&self) {
+        RustBox::clear(self);
+    }
+
+    fn present(&self) {
+        RustBox::present(self);
+    }
+}
+
+/// Converts a Style into its rustbox equivalent
+#[cfg(feature = "rustbox")]
+fn rustbox_style(style: Style) -> rustbox::Style {
+    match style {
+        Style::Normal => rustbox::RB_NORMAL,
+        Style::Bold   => rustbox::RB_BOLD,
+    }
+}
+
+/// Converts a Color into its rustbox equivalent
+#[cfg(feature = "rustbox")]
+fn rustbox_color(color: Color) -> rustbox::Color {
+    match color {
+        Color::Black   => rustbox::Color::Black,
+        Color::Red     => rustbox::Color::Red,
+        Color::Green   => rustbox::Color::Green,
+        Color::Yellow  => rustbox::Color::Yellow,
+        Color::Blue    => rustbox::Color::Blue,
+        Color::Magenta => rustbox::Color::Magenta,
+        Color::Cyan    => rustbox::Color::Cyan,
+        Color::White   => rustbox::Color::White,
+    }
+}
+
+/// A Surface drawn to the terminal through crossterm. The terminal is taken over
+/// when the Crossterm struct is initialized and restored when it is dropped.
+///
+/// Terminals that support the kitty keyboard protocol are asked to report key
+/// repeats and releases as well as presses, along with modifier keys such as Shift
+#[cfg(feature = "crossterm")]
+pub struct Crossterm {
+    out: RefCell<BufWriter<Stdout>>,
+    is_enhanced: bool,
+}
+
+#[cfg(feature = "crossterm")]
+impl Crossterm {
+
+    /// Initializes a new Crossterm struct
+    pub fn init() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+
+        // The terminal has to be queried in raw mode. Any failure to answer is
+        // treated the same as the protocol being unsupported
+        let is_enhanced = terminal::supports_keyboard_enhancement().unwrap_or(false);
+
+        let mut out = BufWriter::new(io::stdout());
+        out.queue(terminal::EnterAlternateScreen)?
+            .queue(cursor::Hide)?
+            .queue(terminal::Clear(terminal::ClearType::All))?;
+
+        if is_enhanced {
+            out.queue(event::PushKeyboardEnhancementFlags(
+                event::KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES
+                    | event::KeyboardEnhancementFlags::REPORT_EVENT_TYPES
+                    | event::KeyboardEnhancementFlags::REPORT_ALL_KEYS_AS_ESCAPE_CODES
+            ))?;
+        }
+
+        out.flush()?;
+
+        Ok(Crossterm {
+            out: RefCell::new(out),
+            is_enhanced: is_enhanced,
+        })
+    }
+
+    // GETTERS / SETTERS
+
+    pub fn is_enhanced(&self) -> bool {
+        self.is_enhanced
+    }
+}
+
+#[cfg(feature = "crossterm")]
+impl Drop for Crossterm {
+    fn drop(&mut self) {
+        let mut out = self.out.borrow_mut();
+
+        if self.is_enhanced {
+            let _ = out.queue(event::PopKeyboardEnhancementFlags);
+        }
+
+        // There is nothing left to do if the terminal can't be restored
+        let _ = out.queue(cursor::Show).and_then(|out| out.queue(terminal::LeaveAlternateScreen));
+        let _ = out.flush();
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+#[cfg(feature = "crossterm")]
+impl Surface for Crossterm {
+    fn print_char(&self, x: usize, y: usize, style: Style, fg: Color, bg: Color, c: char) {
+        self.print(x, y, style, fg, bg, c.encode_utf8(&mut [0; 4]));
+    }
+
+    fn print(&self, x: usize, y: usize, style: Style, fg: Color, bg: Color, s: &str) {
+        let mut out = self.out.borrow_mut();
+
+        // Drawing is queued until the Surface is presented, so any errors will surface then
+        let _ = out.queue(cursor::MoveTo(x as u16, y as u16))
+            .and_then(|out| out.queue(style::SetAttribute(style::Attribute::Reset)))
+            .and_then(|out| out.queue(style::SetAttribute(crossterm_style(style))))
+            .and_then(|out| out.queue(style::SetForegroundColor(crossterm_color(fg))))
+            .and_then(|out| out.queue(style::SetBackgroundColor(crossterm_color(bg))))
+            .and_then(|out| out.queue(style::Print(s)));
+    }
+
+    fn clear(&self) {
+        let _ = self.out.borrow_mut()
+            .queue(style::SetAttribute(style::Attribute::Reset))
+            .and_then(|out| out.queue(terminal::Clear(terminal::ClearType::All)));
+    }
+
+    fn present(&self) {
+        let _ = self.out.borrow_mut().flush();
+    }
+}
+
+/// Converts a Style into its crossterm equivalent
+#[cfg(feature = "crossterm")]
+fn crossterm_style(style: Style) -> style::Attribute {
+    match style {
+        Style::Normal => style::Attribute::NormalIntensity,
+        Style::Bold   => style::Attribute::Bold,
+    }
+}
+
+/// Converts a Color into its crossterm equivalent
+#[cfg(feature = "crossterm")]
+fn crossterm_color(color: Color) -> style::Color {
+    match color {
+        Color::Black   => style::Color::Black,
+        Color::Red     => style::Color::DarkRed,
+        Color::Green   => style::Color::DarkGreen,
+        Color::Yellow  => style::Color::DarkYellow,
+        Color::Blue    => style::Color::DarkBlue,
+        Color::Magenta => style::Color::DarkMagenta,
+        Color::Cyan    => style::Color::DarkCyan,
+        Color::White   => style::Color::Grey,
+    }
+}
@@ -1,10 +1,8 @@
-extern crate rustbox;
-
 use std::ops::Add;
 
-const MINOS: usize = 4;
+pub const MINOS: usize = 4;
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, PartialEq, Debug)]
 pub struct Tetromino {
     minos: [Point; MINOS],
     origin: Point,
@@ -30,7 +28,7 @@ pub enum TetrominoType {
     Ghost,
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, PartialEq, Debug)]
 pub enum Rotation {
     Spawn,
     Right,
@@ -42,14 +40,25 @@ impl Tetromino {
 
     /// Initializes a new Tetromino struct of a specified type and rotation
     pub fn new(origin: Point, tetromino_type: TetrominoType, rot: Rotation) -> Self {
-        let mut tetromino = TETROMINOS[tetromino_type as usize][rot as usize].clone();
+        let mut tetromino = TETROMINOS[tetromino_type as usize][rot as usize];
         tetromino.origin = origin;
         tetromino
     }
 
+    /// Initializes a new Tetromino struct of a specified type and rotation with its own
+    /// minos, for rotation systems whose shapes differ from the standard ones
+    pub fn from_minos(origin: Point, tetromino_type: TetrominoType, rot: Rotation, minos: [Point; MINOS]) -> Self {
+        Tetromino {
+            minos: minos,
+            origin: origin,
+            tetromino_type: tetromino_type,
+            rot: rot,
+        }
+    }
+
     /// Initializes a new ghost Tetromino struct
     pub fn new_ghost(tetromino: &Tetromino) -> Self {
-        let mut ghost = tetromino.clone();
+        let mut ghost = *tetromino;
         ghost.tetromino_type = TetrominoType::Ghost;
         ghost
     }
@@ -1,90 +1,206 @@
-extern crate rustbox;
+use yatc_rs::board::{Board, Phase, TopOut};
+use yatc_rs::mode;
+use yatc_rs::scoring::Clear;
+use yatc_rs::tetromino::{Tetromino, TetrominoType};
 
-use self::rustbox::{Color, Style, RustBox};
-
-use super::board::{Board, HEIGHT, WIDTH};
-use super::tetromino::{Tetromino, TetrominoType};
+use super::menu::Menu;
+use super::scores::{Entry, TABLE_SIZE};
+use super::surface::{Color, Style, Surface};
 use super::window::Window;
 
 // Default scaling factor for the board
 const SCALE: usize = 2;
 
+// Number of rows taken up by each Tetromino in the next window
+const PREVIEW_HEIGHT: usize = 3;
+
+// Number of stats from the GameMode shown beside the board and on the results screen
+const HUD_FIELDS: usize = 3;
+const RESULT_FIELDS: usize = 5;
+
+// Width of the labelled values on the results screen
+const RESULT_WIDTH: usize = 16;
+
+// Size of the menu shown over the board while paused
+const PAUSE_WIDTH: usize = 15;
+const PAUSE_HEIGHT: usize = 8;
+
+// Size of the results shown over the board once the game is over
+const GAME_OVER_WIDTH: usize = 19;
+const GAME_OVER_HEIGHT: usize = 15;
+
+// Position and width of the menus shown outside of a game
+const MENU_X: usize = 2;
+const MENU_Y: usize = 5;
+const MENU_WIDTH: usize = 30;
+
+// Width of the high score tables
+const SCORES_WIDTH: usize = 60;
+
+// Number of frames that cleared lines stay lit or unlit while flashing
+const FLASH_FRAMES: usize = 4;
+
 // Default values for styling terminal output
-const DEFAULT_STYLE: Style = rustbox::RB_NORMAL;
+const DEFAULT_STYLE: Style = Style::Normal;
 const DEFAULT_FG: Color = Color::White;
 const DEFAULT_BG: Color = Color::Black;
 
 /// A collection of Window structs representing the user interface
 pub struct Ui<'a> {
+    surface: &'a dyn Surface,
     board: Window<'a>,
-    score: Window<'a>,
-    level: Window<'a>,
-    lines: Window<'a>,
-    next: Window<'a>,
+    hud: Vec<Window<'a>>,
+    next: Option<Window<'a>>,
     hold: Window<'a>,
+    clear: Window<'a>,
+    replay: Window<'a>,
+    pause: Window<'a>,
+    game_over: Window<'a>,
 }
 
 impl<'a> Ui<'a> {
 
-    /// Initializes a new Ui struct
-    pub fn new(rb: &'a RustBox) -> Self {
+    /// Initializes a new Ui struct showing a number of next Tetrominos, for a board
+    /// of a specified width and number of visible rows
+    pub fn new(surface: &'a dyn Surface, previews: usize, width: usize, rows: usize) -> Self {
+
+        // The board is drawn with each cell two characters wide inside its borders,
+        // and the other windows are laid out to the right of it
+        let board_w = (width * SCALE) + 1;
+        let board_h = rows + 1;
+        let side = board_w + 3;
+
+        // The next window is only shown if there are Tetrominos to preview
+        let next = if previews > 0 {
+            Some(Window::new(side + (6 * SCALE), 5, (5 * SCALE) + 1, (previews * PREVIEW_HEIGHT) + 1, surface))
+        } else {
+            None
+        };
+
+        // Each field of the HUD is a label with its value on the row below
+        let hud = (0..HUD_FIELDS)
+            .map(|i| Window::new(side, 6 + (i * 4), 11, 2, surface))
+            .collect();
+
         Ui {
-            board: Window::new(0, 5, (11 * SCALE) - 1, 21, rb),
-            score: Window::new(12 * SCALE, 6, 11, 1, rb),
-            level: Window::new(12 * SCALE, 10, 11, 1, rb),
-            lines: Window::new(12 * SCALE, 14, 11, 1, rb),
-            next:  Window::new(5 , 1, (5 * SCALE) + 1, 4, rb),
-            hold: Window::new(12 * SCALE, 18, (5 * SCALE) + 1, 5, rb),
+            surface: surface,
+            board: Window::new(0, 5, board_w, board_h, surface),
+            hud: hud,
+            next: next,
+            hold: Window::new(side, 18, (5 * SCALE) + 1, 5, surface),
+            clear: Window::new(side, 25, 22, 3, surface),
+            replay: Window::new(side, 29, 22, 2, surface),
+            pause: Window::new(board_w.saturating_sub(PAUSE_WIDTH) / 2, 5 + (board_h.saturating_sub(PAUSE_HEIGHT) / 2),
+                               PAUSE_WIDTH, PAUSE_HEIGHT, surface),
+            game_over: Window::new(board_w.saturating_sub(GAME_OVER_WIDTH) / 2, 5 + (board_h.saturating_sub(GAME_OVER_HEIGHT) / 2),
+                                   GAME_OVER_WIDTH, GAME_OVER_HEIGHT, surface),
         }
     }
 
+    /// Shows everything printed since the last call
+    pub fn present(&self) {
+        self.surface.present();
+    }
+
+    /// Erases the whole user interface
+    pub fn clear(&self) {
+        self.surface.clear();
+    }
+
+    /// Prints a Menu on its own, such as the title menu
+    pub fn print_menu(&self, menu: &Menu) {
+        self.surface.clear();
+        menu.print(&Window::new(MENU_X, MENU_Y, MENU_WIDTH, menu.height(), self.surface));
+    }
+
     /// Setup the default elements of the user interface 
     pub fn setup(&self) {
         self.board.print_borders(DEFAULT_STYLE, DEFAULT_FG, DEFAULT_BG);
-        self.next.print_borders(DEFAULT_STYLE, DEFAULT_FG, DEFAULT_BG);
         self.hold.print_borders(DEFAULT_STYLE, DEFAULT_FG, DEFAULT_BG);
-        self.print_score(0);
-        self.print_level(0);
-        self.print_lines(0);
     }
 
     /// Resets some of the user interface elements
     pub fn reset(&self) {
-        self.score.clear();
-        self.level.clear();
-        self.lines.clear();
+        for window in self.hud.iter() {
+            window.clear();
+        }
+
+        self.clear.clear();
 
-        self.print_score(0);
-        self.print_level(0);
-        self.print_lines(0);
+        self.hold.clear();
+        self.hold.print_borders(DEFAULT_STYLE, DEFAULT_FG, DEFAULT_BG);
     }
 
-    /// Print the state of the board
+    /// Print the state of the board, animating any lines being cleared
     pub fn print_board(&self, board: &Board) {
+        let mut field = board.render_field();
+        let (is_lit, vanished) = self.clear_animation(board);
+
+        let width = board.width();
+
+        for &y in board.clearing().iter() {
+            for (x, cell) in field[y].iter_mut().enumerate() {
 
-        // Start at 2 because only 20 of the board's rows should be displayed
-        for y in 2..HEIGHT {
-            for x in 0..WIDTH {
-                match board.field()[y][x] {
+                // Distance from the middle column or two of the line
+                let distance = if x < width / 2 { (width / 2) - 1 - x } else { x - (width / 2) };
+
+                if distance < vanished {
+                    *cell = None;
+                }
+            }
+        }
+
+        // Start at the skyline because the rows above it shouldn't be displayed
+        for (y, cells) in field.iter().enumerate().skip(board.skyline()) {
+            let is_flash = is_lit && board.clearing().contains(&y);
+            let row = y - board.skyline() + 1;
+
+            for (x, cell) in cells.iter().enumerate() {
+                match *cell {
 
                     // When printing the board, offset x and y to compensate
-                    // for the Window's borders and showing only 20 rows
+                    // for the Window's borders and the hidden rows
                     Some(ref mino) =>  {
-                        let color = self.get_tetromino_color(mino);
+                        let (style, color) = if is_flash {
+                            (Style::Bold, Color::White)
+                        } else {
+                            (DEFAULT_STYLE, self.get_tetromino_color(mino))
+                        };
+
                         let rune = self.get_tetromino_rune(mino);
-                        self.board.print_char((x * SCALE) + 1, y - 1, DEFAULT_STYLE, color, DEFAULT_BG, rune);
-                        self.board.print_char((x * SCALE) + 2, y - 1, DEFAULT_STYLE, color, DEFAULT_BG, rune);
+                        self.board.print_char((x * SCALE) + 1, row, style, color, DEFAULT_BG, rune);
+                        self.board.print_char((x * SCALE) + 2, row, style, color, DEFAULT_BG, rune);
                     }
 
                     None => {
-                        self.board.print_char((x * SCALE) + 1, y - 1, DEFAULT_STYLE, DEFAULT_FG, DEFAULT_BG, ' ');
-                        self.board.print_char((x * SCALE) + 2, y - 1, DEFAULT_STYLE, DEFAULT_FG, DEFAULT_BG, '.');
+                        self.board.print_char((x * SCALE) + 1, row, DEFAULT_STYLE, DEFAULT_FG, DEFAULT_BG, ' ');
+                        self.board.print_char((x * SCALE) + 2, row, DEFAULT_STYLE, DEFAULT_FG, DEFAULT_BG, '.');
                     },
                 }
             }
         }
     }
 
+    /// Gets the state of the line clear animation, as whether the cleared lines are lit
+    /// up and how many of their columns have vanished on either side of the middle.
+    /// The lines flash for the first half of the line clear delay, then collapse
+    /// from the middle outwards
+    fn clear_animation(&self, board: &Board) -> (bool, usize) {
+        let frames = match board.phase() {
+            Phase::LineClear(frames) => frames,
+            _ => return (false, 0),
+        };
+
+        let flash = board.clear_delay() / 2;
+
+        if frames < flash {
+            return ((frames / FLASH_FRAMES).is_multiple_of(2), 0);
+        }
+
+        let collapse = board.clear_delay() - flash;
+        (false, ((frames - flash + 1) * board.width().div_ceil(2)) / collapse)
+    }
+
     /// Gets the character associated with a TetrominoType
     fn get_tetromino_rune(&self, tetromino_type: &TetrominoType) -> char {
         match tetromino_type {
@@ -106,42 +222,237 @@ impl<'a> Ui<'a> {
         }
     }
 
-    /// Prints the next Tetromino
-    pub fn print_next(&self, tetromino: Tetromino) {
-        self.print_tetromino(tetromino, &self.next);
+    /// Prints the next Tetrominos stacked from top to bottom
+    pub fn print_next(&self, tetrominos: &[Tetromino]) {
+        if let Some(ref next) = self.next {
+            next.clear();
+            next.print_borders(DEFAULT_STYLE, DEFAULT_FG, DEFAULT_BG);
+
+            for (i, &tetromino) in tetrominos.iter().enumerate() {
+                self.print_tetromino(tetromino, next, i * PREVIEW_HEIGHT);
+            }
+        }
     }
 
     /// Prints the hold Tetromino
     pub fn print_hold(&self, hold: Option<Tetromino>) {
         if let Some(tetromino) = hold {
-            self.print_tetromino(tetromino, &self.hold);
+            self.hold.clear();
+            self.hold.print_borders(DEFAULT_STYLE, DEFAULT_FG, DEFAULT_BG);
+            self.print_tetromino(tetromino, &self.hold, 0);
         }
     }
 
-    // Prints a Tetromino to a specified Window
-    fn print_tetromino(&self, tetromino: Tetromino, window: &Window) {
-        window.clear();
-        window.print_borders(DEFAULT_STYLE, DEFAULT_FG, DEFAULT_BG);
+    // Prints a Tetromino to a specified Window, starting at a row. Its top row of
+    // minos is always printed on the first row, whatever the rotation system
+    fn print_tetromino(&self, tetromino: Tetromino, window: &Window, row: usize) {
+        let top = tetromino.minos().iter().map(|mino| mino.y).min().unwrap_or(0);
 
         for &mino in tetromino.minos().iter() {
             let color = self.get_tetromino_color(&tetromino.tetromino_type());
-            window.print_char(((mino.x as usize) * SCALE + 2), (mino.y + 1) as usize, DEFAULT_STYLE, color, DEFAULT_BG, '■');
-            window.print_char(((mino.x as usize) * SCALE + 3), (mino.y + 1) as usize, DEFAULT_STYLE, color, DEFAULT_BG, '■');
+            let y = row + (mino.y - top + 1) as usize;
+            window.print_char((mino.x as usize) * SCALE + 2, y, DEFAULT_STYLE, color, DEFAULT_BG, '■');
+            window.print_char((mino.x as usize) * SCALE + 3, y, DEFAULT_STYLE, color, DEFAULT_BG, '■');
         }
     }
 
-    /// Prints the player's score
-    pub fn print_score(&self, score: usize) {
-        self.score.print(0, 0, DEFAULT_STYLE, DEFAULT_FG, DEFAULT_BG, &format!("{:}", score));
+    /// Prints the stats that the GameMode shows beside the board, such as the score
+    pub fn print_hud(&self, fields: &[mode::Stat]) {
+        for (window, &(label, ref value)) in self.hud.iter().zip(fields.iter()) {
+            window.clear();
+            window.print(0, 0, Style::Bold, DEFAULT_FG, DEFAULT_BG, label);
+            window.print(0, 1, DEFAULT_STYLE, DEFAULT_FG, DEFAULT_BG, value);
+        }
     }
 
-    /// Prints the difficulty level
-    pub fn print_level(&self, level: usize) {
-        self.level.print(0, 0, DEFAULT_STYLE, DEFAULT_FG, DEFAULT_BG, &format!("{:}", level));
+    /// Prints banners for the last clear and the current combo
+    pub fn print_clear(&self, clear: Option<Clear>, combo: Option<usize>) {
+        self.clear.clear();
+
+        if let Some(clear) = clear {
+            let name = if clear.back_to_back {
+                format!("B2B {:}", clear.name())
+            } else {
+                clear.name().to_string()
+            };
+
+            self.clear.print(0, 0, DEFAULT_STYLE, DEFAULT_FG, DEFAULT_BG, &name);
+
+            if clear.perfect_clear {
+                self.clear.print(0, 2, DEFAULT_STYLE, DEFAULT_FG, DEFAULT_BG, "PERFECT CLEAR");
+            }
+        }
+
+        if let Some(combo) = combo {
+            if combo > 0 {
+                self.clear.print(0, 1, DEFAULT_STYLE, DEFAULT_FG, DEFAULT_BG, &format!("{:} COMBO", combo));
+            }
+        }
+    }
+
+    /// Prints the state of a replay being played back, as how far into the game it is
+    /// and whether it is paused, finished or sped up
+    pub fn print_replay(&self, frames: usize, speed: usize, is_paused: bool, is_finished: bool) {
+        let state = if is_finished {
+            String::from("END")
+        } else if is_paused {
+            String::from("PAUSED")
+        } else {
+            format!("{}X", speed)
+        };
+
+        self.replay.clear();
+        self.replay.print(0, 0, Style::Bold, DEFAULT_FG, DEFAULT_BG, &format!("REPLAY {}", state));
+        self.replay.print(0, 1, DEFAULT_STYLE, DEFAULT_FG, DEFAULT_BG, &mode::format_time(frames));
+    }
+
+    /// Prints the pause Menu over the board, which is hidden while the game is paused
+    pub fn print_pause(&self, menu: &Menu) {
+        self.board.clear();
+        self.board.print_borders(DEFAULT_STYLE, DEFAULT_FG, DEFAULT_BG);
+        menu.print(&self.pause);
+    }
+
+    /// Prints a high score table for a mode played by a set of rules
+    pub fn print_high_scores(&self, mode: &str, rules: &str, entries: &[Entry]) {
+        let window = Window::new(MENU_X, MENU_Y, SCORES_WIDTH, TABLE_SIZE + 7, self.surface);
+
+        self.surface.clear();
+        window.print_borders(DEFAULT_STYLE, DEFAULT_FG, DEFAULT_BG);
+        window.print(2, 1, Style::Bold, DEFAULT_FG, DEFAULT_BG, &format!("HIGH SCORES  {}", mode));
+
+        // Cut off the rules rather than leave them out if there are too many to fit
+        let rules: String = rules.to_uppercase().chars().take(SCORES_WIDTH - 3).collect();
+        window.print(2, 2, DEFAULT_STYLE, DEFAULT_FG, DEFAULT_BG, &rules);
+
+        let header = format!("{:>2} {:<8} {:>9} {:>5} {:>3} {:>8} {:>5} {}", "#", "NAME", "SCORE", "LINES", "LV", "TIME", "PPS", "DATE");
+        window.print(2, 4, Style::Bold, DEFAULT_FG, DEFAULT_BG, &header);
+
+        for (i, entry) in entries.iter().take(TABLE_SIZE).enumerate() {
+            let row = format!("{:>2} {:<8} {:>9} {:>5} {:>3} {:>8} {:>5.2} {}", i + 1, entry.name, entry.score, entry.lines,
+                              entry.level, mode::format_time(entry.frames), entry.pps, entry.date);
+
+            window.print(2, 5 + i, DEFAULT_STYLE, DEFAULT_FG, DEFAULT_BG, &row);
+        }
+
+        if entries.is_empty() {
+            window.print(2, 5, DEFAULT_STYLE, DEFAULT_FG, DEFAULT_BG, "NO SCORES YET");
+        }
+
+        window.print(2, TABLE_SIZE + 6, DEFAULT_STYLE, DEFAULT_FG, DEFAULT_BG, "LEFT/RIGHT: MODE   ESC: BACK");
     }
 
-    /// Prints the number of lines cleared
-    pub fn print_lines(&self, lines: usize) {
-        self.lines.print(0, 0, DEFAULT_STYLE, DEFAULT_FG, DEFAULT_BG, &format!("{:}", lines));
+    /// Prints the results of a finished game over the board, as chosen by its GameMode,
+    /// followed by a Menu of what to do next and a notice if its high score wasn't saved
+    pub fn print_game_over(&self, board: &Board, seed: u64, menu: &Menu, is_unsaved: bool) {
+        self.print_results(board, seed);
+
+        if is_unsaved {
+            self.game_over.print(2, 11, Style::Bold, Color::Red, DEFAULT_BG, "SCORE NOT SAVED");
+        }
+
+        menu.print_items(&self.game_over, 12);
     }
-}
\ No newline at end of file
+
+    /// Prints the results of a finished game that made a high score table, followed by
+    /// a prompt for the player's name
+    pub fn print_name_entry(&self, board: &Board, seed: u64, rank: usize, name: &str) {
+        self.print_results(board, seed);
+        self.game_over.print(2, 12, Style::Bold, DEFAULT_FG, DEFAULT_BG, &format!("HIGH SCORE #{}", rank + 1));
+        self.game_over.print(2, 13, DEFAULT_STYLE, DEFAULT_FG, DEFAULT_BG, &format!("NAME {}_", name));
+    }
+
+    /// Prints the results of a finished game over the board, as chosen by its GameMode
+    fn print_results(&self, board: &Board, seed: u64) {
+        self.game_over.clear();
+        self.game_over.print_borders(DEFAULT_STYLE, DEFAULT_FG, DEFAULT_BG);
+
+        // A completed game is headed by its mode, and a lost one by how it was lost
+        if let Some(top_out) = board.top_out() {
+            self.game_over.print(2, 1, Style::Bold, DEFAULT_FG, DEFAULT_BG, "GAME OVER");
+            self.game_over.print(2, 2, DEFAULT_STYLE, DEFAULT_FG, DEFAULT_BG, self.get_top_out_name(top_out));
+        }
+
+        else {
+            let ending = if board.is_complete() { "COMPLETE" } else { "ENDED" };
+            self.game_over.print(2, 1, Style::Bold, DEFAULT_FG, DEFAULT_BG, board.mode().name());
+            self.game_over.print(2, 2, DEFAULT_STYLE, DEFAULT_FG, DEFAULT_BG, ending);
+        }
+
+        for (i, &(label, ref value)) in board.mode().results(board).iter().take(RESULT_FIELDS).enumerate() {
+            let width = RESULT_WIDTH.saturating_sub(label.len() + 1);
+            self.game_over.print(2, 4 + i, DEFAULT_STYLE, DEFAULT_FG, DEFAULT_BG, &format!("{} {:>width$}", label, value, width = width));
+        }
+
+        self.game_over.print(2, 9, DEFAULT_STYLE, DEFAULT_FG, DEFAULT_BG, "SEED");
+        self.game_over.print(2, 10, DEFAULT_STYLE, DEFAULT_FG, DEFAULT_BG, &format!("{:>16}", seed));
+    }
+
+    /// Gets the name shown for the way in which the stack topped out
+    fn get_top_out_name(&self, top_out: TopOut) -> &'static str {
+        match top_out {
+            TopOut::BlockOut => "BLOCK OUT",
+            TopOut::LockOut => "LOCK OUT",
+            TopOut::PartialLockOut => "PARTIAL LOCK OUT",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use yatc_rs::board::Board;
+    use yatc_rs::randomizer::Bag;
+    use yatc_rs::srs::{HalfKicks, Srs};
+
+    use super::Ui;
+    use super::super::surface::{Buffer, Color};
+
+    #[test]
+    fn board_snapshot() {
+
+        // Lock a T against the left wall, then hold the O that follows it
+        let mut board = Board::with_size(Box::new(Bag::new(1, 42)), Box::new(Srs::new(HalfKicks::Tetrio)), 6, 6, 2);
+        board.left();
+        board.drop_tetromino();
+        board.update();
+        board.hold_tetromino();
+
+        let buffer = Buffer::new(40, 24);
+        let ui = Ui::new(&buffer, 2, board.width(), board.height() - board.skyline());
+        ui.setup();
+        ui.print_board(&board);
+        ui.print_next(&board.peek_next(2));
+        ui.print_hold(board.hold());
+
+        assert_eq!(buffer.lines(), [
+            "",
+            "",
+            "",
+            "",
+            "",
+            "┌────────────┐              ┌──────────┐",
+            "│ . . . . . .│              │     ■■   │",
+            "│ .□□□□□□□□ .│              │ ■■■■■■   │",
+            "│ .■■ . . . .│              │          │",
+            "│■■■■■■ . . .│              │   ■■■■   │",
+            "└────────────┘              │ ■■■■     │",
+            "                            │          │",
+            "                            └──────────┘",
+            "",
+            "",
+            "",
+            "",
+            "",
+            "                ┌──────────┐",
+            "                │   ■■■■   │",
+            "                │   ■■■■   │",
+            "                │          │",
+            "                │          │",
+            "                └──────────┘",
+        ]);
+
+        // The locked T keeps its color, while the ghost of the I above it is drawn in white
+        assert_eq!(buffer.cell(1, 9).map(|cell| cell.fg), Some(Color::Magenta));
+        assert_eq!(buffer.cell(3, 7).map(|cell| cell.fg), Some(Color::White));
+    }
+}
@@ -1,49 +1,48 @@
-extern crate rustbox;
-
-use self::rustbox::{Color, Style, RustBox};
+use super::surface::{Color, Style, Surface};
 
 /// A drawable area and element of the screen.
 ///
 /// The origin of a Window is (0, 0) and is located at the top left. All
-/// drawing operations are done relative to this coordinate position
+/// drawing operations are done relative to this coordinate position and
+/// are drawn through to the underlying Surface
 pub struct Window<'a> {
     pub x: usize,
     pub y: usize,
     pub w: usize,
     pub h: usize,
-    rb: &'a RustBox,
+    surface: &'a dyn Surface,
 }
 
 impl<'a> Window<'a> {
 
     /// Initializes a new Window struct at an (x, y) position with a specified width and height
-    pub fn new(x: usize, y: usize, w: usize, h: usize, rb: &'a RustBox) -> Self {
+    pub fn new(x: usize, y: usize, w: usize, h: usize, surface: &'a dyn Surface) -> Self {
         Window {
             x: x,
             y: y,
             w: w,
             h: h,
-            rb: rb,
+            surface: surface,
         }
     }
 
     /// Prints a character at an (x, y) position
     pub fn print_char(&self, x: usize, y: usize, style: Style, fg: Color, bg: Color, c: char) {
         if x <= self.w && y <= self.h {
-            self.rb.print_char(self.x + x, self.y + y, style, fg, bg, c);
+            self.surface.print_char(self.x + x, self.y + y, style, fg, bg, c);
         }
     }
 
     /// Prints a string at an (x, y) position
     pub fn print(&self, x: usize, y: usize, style: Style, fg: Color, bg: Color, s: &str) {
         if x + s.len() <= self.w && y <= self.h {
-            self.rb.print(self.x + x, self.y + y, style, fg, bg, s);
+            self.surface.print(self.x + x, self.y + y, style, fg, bg, s);
         }
     }
 
     /// Erases a character at an (x, y) position
     pub fn erase(&self, x: usize, y: usize) {
-        self.print_char(x, y, rustbox::RB_NORMAL, Color::Black, Color::Black, ' ');
+        self.print_char(x, y, Style::Normal, Color::Black, Color::Black, ' ');
     }
 
     /// Erases the area of a Window